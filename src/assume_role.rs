@@ -0,0 +1,247 @@
+//! Read secrets and parameters from another account, by assuming an IAM role.
+//!
+//! An [`AssumeRole`] describes the role to assume (with an optional external
+//! id and session name). It can be attached to the shared config once, with
+//! [`AssumeRole::config`], or per lookup through a [`RoleConfigs`] cache,
+//! which keeps one config per role. In both cases the temporary credentials
+//! are cached, and only refreshed shortly before they expire.
+//!
+//! ```no_run
+//! use aws_secrets::assume_role::{AssumeRole, RoleConfigs};
+//!
+//! # async fn run() {
+//! let roles = RoleConfigs::new(aws_secrets::config_from_env().await);
+//! let security = AssumeRole::new("arn:aws:iam::123456789012:role/read-secrets")
+//!     .external_id("my-external-id");
+//!
+//! // Pass this config to `SecretsExt` or `SSMParamExt` methods.
+//! let config = roles.config_for(&security).await;
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use aws_config::sts::AssumeRoleProvider;
+use aws_config::SdkConfig;
+use aws_credential_types::provider::{self, ProvideCredentials, SharedCredentialsProvider};
+use aws_credential_types::Credentials;
+
+/// The session name used when none is set.
+pub const DEFAULT_SESSION_NAME: &str = "aws-secrets";
+
+/// Credentials are refreshed when they expire within this window.
+const REFRESH_WINDOW: Duration = Duration::from_secs(5 * 60);
+
+/// An IAM role to assume; see the [module docs](self).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AssumeRole {
+    role_arn: String,
+    external_id: Option<String>,
+    session_name: Option<String>,
+    session_length: Option<Duration>,
+}
+
+impl AssumeRole {
+    /// Creates a role to assume, by ARN.
+    pub fn new(role_arn: impl Into<String>) -> Self {
+        Self {
+            role_arn: role_arn.into(),
+            external_id: None,
+            session_name: None,
+            session_length: None,
+        }
+    }
+
+    /// Sets the external id required by the role's trust policy.
+    pub fn external_id(mut self, external_id: impl Into<String>) -> Self {
+        self.external_id = Some(external_id.into());
+        self
+    }
+
+    /// Sets the session name, which shows up in CloudTrail; defaults to
+    /// [`DEFAULT_SESSION_NAME`].
+    pub fn session_name(mut self, session_name: impl Into<String>) -> Self {
+        self.session_name = Some(session_name.into());
+        self
+    }
+
+    /// Sets how long the temporary credentials are valid for; defaults to
+    /// one hour.
+    pub fn session_length(mut self, length: Duration) -> Self {
+        self.session_length = Some(length);
+        self
+    }
+
+    /// Returns the ARN of the role.
+    pub fn role_arn(&self) -> &str {
+        &self.role_arn
+    }
+
+    /// Returns a copy of `base` which assumes this role, using the
+    /// credentials of `base` to call `sts:AssumeRole`.
+    ///
+    /// The returned config can be shared by many lookups: the temporary
+    /// credentials are cached until shortly before they expire.
+    pub async fn config(&self, base: &SdkConfig) -> SdkConfig {
+        let mut builder = AssumeRoleProvider::builder(&self.role_arn)
+            .session_name(self.session_name.as_deref().unwrap_or(DEFAULT_SESSION_NAME))
+            .configure(base);
+        if let Some(external_id) = &self.external_id {
+            builder = builder.external_id(external_id);
+        }
+        if let Some(length) = self.session_length {
+            builder = builder.session_length(length);
+        }
+        let provider = CachedCredentials::new(builder.build().await);
+
+        base.to_builder()
+            .credentials_provider(SharedCredentialsProvider::new(provider))
+            .build()
+    }
+}
+
+/// A cache of configs which assume a role, built from a shared base config.
+///
+/// Cloning a `RoleConfigs` is cheap, and the clones share the cache.
+#[derive(Clone)]
+pub struct RoleConfigs {
+    base: SdkConfig,
+    configs: Arc<Mutex<HashMap<AssumeRole, SdkConfig>>>,
+}
+
+impl RoleConfigs {
+    /// Creates an empty cache. The credentials of `base` are used to
+    /// assume the roles.
+    pub fn new(base: SdkConfig) -> Self {
+        Self {
+            base,
+            configs: Arc::default(),
+        }
+    }
+
+    /// Returns the config which assumes `role`, creating it on first use.
+    pub async fn config_for(&self, role: &AssumeRole) -> SdkConfig {
+        if let Some(config) = self.configs.lock().unwrap().get(role) {
+            return config.clone();
+        }
+
+        let config = role.config(&self.base).await;
+        self.configs
+            .lock()
+            .unwrap()
+            .entry(role.clone())
+            .or_insert(config)
+            .clone()
+    }
+}
+
+impl fmt::Debug for RoleConfigs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let configs = self.configs.lock().unwrap();
+        f.debug_struct("RoleConfigs")
+            .field(
+                "roles",
+                &configs.keys().map(|r| &r.role_arn).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+/// Caches the credentials of a provider until shortly before they expire.
+///
+/// The SDK's own credentials cache lives in each client, and this crate
+/// creates a client per call, so it would not outlive a single lookup.
+#[derive(Debug)]
+struct CachedCredentials<P> {
+    inner: P,
+    cached: Mutex<Option<Credentials>>,
+}
+
+impl<P> CachedCredentials<P> {
+    fn new(inner: P) -> Self {
+        Self {
+            inner,
+            cached: Mutex::new(None),
+        }
+    }
+
+    fn fresh(&self) -> Option<Credentials> {
+        let cached = self.cached.lock().unwrap();
+        cached
+            .as_ref()
+            .filter(|creds| match creds.expiry() {
+                Some(expiry) => expiry > SystemTime::now() + REFRESH_WINDOW,
+                None => true,
+            })
+            .cloned()
+    }
+}
+
+impl<P: ProvideCredentials> ProvideCredentials for CachedCredentials<P> {
+    fn provide_credentials<'a>(&'a self) -> provider::future::ProvideCredentials<'a>
+    where
+        Self: 'a,
+    {
+        provider::future::ProvideCredentials::new(async move {
+            if let Some(creds) = self.fresh() {
+                return Ok(creds);
+            }
+            let creds = self.inner.provide_credentials().await?;
+            *self.cached.lock().unwrap() = Some(creds.clone());
+            Ok(creds)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A provider which counts its calls, and returns credentials which
+    /// expire after `lifetime`.
+    #[derive(Debug)]
+    struct CountingProvider {
+        calls: AtomicUsize,
+        lifetime: Duration,
+    }
+
+    impl ProvideCredentials for CountingProvider {
+        fn provide_credentials<'a>(&'a self) -> provider::future::ProvideCredentials<'a>
+        where
+            Self: 'a,
+        {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            provider::future::ProvideCredentials::ready(Ok(Credentials::new(
+                "AKID",
+                "secret",
+                None,
+                Some(SystemTime::now() + self.lifetime),
+                "test",
+            )))
+        }
+    }
+
+    async fn calls_after_two_lookups(lifetime: Duration) -> usize {
+        let cached = CachedCredentials::new(CountingProvider {
+            calls: AtomicUsize::new(0),
+            lifetime,
+        });
+        cached.provide_credentials().await.unwrap();
+        cached.provide_credentials().await.unwrap();
+        cached.inner.calls.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test]
+    async fn reuses_fresh_credentials() {
+        assert_eq!(calls_after_two_lookups(Duration::from_secs(3600)).await, 1);
+    }
+
+    #[tokio::test]
+    async fn refreshes_expiring_credentials() {
+        assert_eq!(calls_after_two_lookups(Duration::from_secs(60)).await, 2);
+    }
+}
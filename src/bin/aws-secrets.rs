@@ -0,0 +1,451 @@
+//! Command-line interface for `aws-secrets`.
+//!
+//! Requires the `cli` feature to be enabled.
+
+#![deny(warnings)]
+#![warn(rust_2018_idioms)]
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use futures_util::TryStreamExt;
+use serde_json::{Map, Value};
+use structopt::clap::arg_enum;
+use structopt::StructOpt;
+
+use aws_secrets::config::SdkConfig;
+use aws_secrets::template::render_file;
+use aws_secrets::{
+    config_from_env, describe_parameters, Error, ParameterFilter, SSMParamExt, SecretsExt,
+};
+
+/// Exit codes, loosely following the BSD `sysexits.h` conventions.
+mod exit {
+    /// `diff` found differences between the two values.
+    pub const DIFFERENT: u8 = 1;
+    /// Generic failure; distinct from `DIFFERENT`, as with diff(1).
+    pub const FAILURE: u8 = 2;
+    /// The value could not be parsed (e.g. a secret is not valid JSON).
+    pub const DATA_ERR: u8 = 65;
+    /// The secret or parameter does not exist.
+    pub const NO_INPUT: u8 = 66;
+    /// The AWS service could not be reached, or is throttling requests.
+    pub const UNAVAILABLE: u8 = 69;
+    /// The output file could not be written.
+    pub const CANT_CREAT: u8 = 73;
+    /// The caller is not allowed to perform the operation.
+    pub const NO_PERM: u8 = 77;
+}
+
+arg_enum! {
+    /// The AWS service to interact with.
+    #[derive(Debug, Clone, Copy)]
+    enum Service {
+        Sm,
+        Ssm,
+    }
+}
+
+arg_enum! {
+    /// How values are written to stdout.
+    #[derive(Debug, Clone, Copy)]
+    enum Format {
+        Plain,
+        Json,
+        Env,
+    }
+}
+
+arg_enum! {
+    /// Data type of an SSM parameter.
+    #[derive(Debug, Clone, Copy)]
+    enum ParamType {
+        String,
+        SecureString,
+        StringList,
+    }
+}
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "aws-secrets",
+    about = "Interact with AWS Secrets Manager and SSM Parameter Store."
+)]
+struct Opt {
+    /// Output format.
+    #[structopt(
+        short,
+        long,
+        global = true,
+        default_value = "plain",
+        possible_values = &Format::variants(),
+        case_insensitive = true
+    )]
+    format: Format,
+
+    #[structopt(subcommand)]
+    cmd: Command,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Retrieve the value of a secret or parameter.
+    Get {
+        /// Service to read from.
+        #[structopt(possible_values = &Service::variants(), case_insensitive = true)]
+        service: Service,
+        /// Name (or ARN) of the secret or parameter.
+        name: String,
+    },
+    /// Create or update the value of a secret or parameter.
+    Put {
+        /// Service to write to.
+        #[structopt(possible_values = &Service::variants(), case_insensitive = true)]
+        service: Service,
+        /// Name (or ARN) of the secret or parameter.
+        name: String,
+        /// New value. For a `StringList` parameter, separate items with a comma.
+        value: String,
+        /// Data type of the parameter (SSM only).
+        #[structopt(
+            short = "t",
+            long = "type",
+            default_value = "SecureString",
+            possible_values = &ParamType::variants(),
+            case_insensitive = true
+        )]
+        param_type: ParamType,
+    },
+    /// List secrets by name prefix, or parameters under a path.
+    List {
+        /// Service to list from.
+        #[structopt(possible_values = &Service::variants(), case_insensitive = true)]
+        service: Service,
+        /// Name prefix (Secrets Manager) or path (SSM).
+        #[structopt(default_value = "")]
+        prefix: String,
+    },
+    /// Set or update the value of a tag on a secret or parameter.
+    Tag {
+        /// Service the resource lives in.
+        #[structopt(possible_values = &Service::variants(), case_insensitive = true)]
+        service: Service,
+        /// Name (or ARN) of the secret or parameter.
+        name: String,
+        /// Tag key.
+        key: String,
+        /// Tag value.
+        value: String,
+    },
+    /// Compare the values of two secrets or parameters.
+    Diff {
+        /// Service to read from.
+        #[structopt(possible_values = &Service::variants(), case_insensitive = true)]
+        service: Service,
+        /// Name of the first secret or parameter.
+        left: String,
+        /// Name of the second secret or parameter.
+        right: String,
+    },
+    /// Render a template, replacing `{{ sm:<secret-id>#<field> }}` and
+    /// `{{ ssm:<parameter-name> }}` placeholders with their values.
+    Render {
+        /// Path to the template file.
+        #[structopt(parse(from_os_str))]
+        input: PathBuf,
+        /// File to write the rendered output to, instead of stdout.
+        #[structopt(short, long, parse(from_os_str))]
+        output: Option<PathBuf>,
+    },
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let Opt { format, cmd } = Opt::from_args();
+
+    let config = config_from_env().await;
+
+    match run(&config, format, cmd).await {
+        Ok(code) => ExitCode::from(code),
+        Err(e) => {
+            eprintln!("error: {e}");
+            let mut source = std::error::Error::source(&e);
+            while let Some(cause) = source {
+                eprintln!("  caused by: {cause}");
+                source = cause.source();
+            }
+            ExitCode::from(exit_code(&e))
+        }
+    }
+}
+
+/// Maps a library error to a process exit code.
+fn exit_code(e: &Error) -> u8 {
+    if e.is_not_found() {
+        return exit::NO_INPUT;
+    }
+    // Network errors, timeouts, throttling and server-side errors.
+    if e.is_transient() {
+        return exit::UNAVAILABLE;
+    }
+    match e {
+        Error::DeserializeError(_) | Error::MissingField { .. } | Error::Template { .. } => {
+            return exit::DATA_ERR;
+        }
+        // Only input files are read through the library; see `Command::Render`
+        // for output files.
+        Error::Io(_) => return exit::NO_INPUT,
+        _ => {}
+    }
+    match e.code() {
+        Some("AccessDeniedException" | "AccessDenied") => exit::NO_PERM,
+        // `is_transient` doesn't classify the errors of write operations.
+        Some("ThrottlingException" | "InternalServiceError" | "InternalServerError") => {
+            exit::UNAVAILABLE
+        }
+        _ => exit::FAILURE,
+    }
+}
+
+async fn run(config: &SdkConfig, format: Format, cmd: Command) -> aws_secrets::Result<u8> {
+    match cmd {
+        Command::Get { service, name } => {
+            let value = get(config, service, &name).await?;
+            print_values(format, &[(name, value)]);
+        }
+        Command::Put {
+            service,
+            name,
+            value,
+            param_type,
+        } => {
+            let name = name.as_str();
+            match service {
+                Service::Sm => {
+                    name.put_secret_string(config, &value).await?;
+                }
+                Service::Ssm => {
+                    match param_type {
+                        ParamType::String => name.put_string(config, &value).await?,
+                        ParamType::SecureString => name.put_secure_string(config, &value).await?,
+                        ParamType::StringList => {
+                            let values: Vec<&str> = value.split(',').collect();
+                            name.put_string_list(config, &values).await?
+                        }
+                    };
+                }
+            }
+        }
+        Command::List { service, prefix } => {
+            let names = match service {
+                Service::Sm => {
+                    prefix
+                        .as_str()
+                        .list_secrets(config)
+                        .map_ok(|secret| secret.name)
+                        .try_collect()
+                        .await?
+                }
+                Service::Ssm => {
+                    // Only the names are needed, so the values aren't fetched
+                    // (or decrypted).
+                    let filter = if prefix.is_empty() {
+                        ParameterFilter::new()
+                    } else {
+                        ParameterFilter::new().path(&prefix, true)
+                    };
+                    describe_parameters(config, &filter)
+                        .map_ok(|param| param.name)
+                        .try_collect()
+                        .await?
+                }
+            };
+            match format {
+                Format::Json => println!("{}", Value::from(names)),
+                _ => names.iter().for_each(|name| println!("{name}")),
+            }
+        }
+        Command::Tag {
+            service,
+            name,
+            key,
+            value,
+        } => {
+            let name = name.as_str();
+            match service {
+                Service::Sm => {
+                    name.set_tag(config, &key, &value).await?;
+                }
+                Service::Ssm => {
+                    name.set_param_tag(config, &key, &value).await?;
+                }
+            }
+        }
+        Command::Diff {
+            service,
+            left,
+            right,
+        } => {
+            let (left_value, right_value) =
+                tokio::try_join!(get(config, service, &left), get(config, service, &right))?;
+            let changes = diff(&left_value, &right_value);
+            for change in &changes {
+                println!("{change}");
+            }
+            if !changes.is_empty() {
+                return Ok(exit::DIFFERENT);
+            }
+        }
+        Command::Render { input, output } => {
+            let rendered = render_file(input, config).await?;
+            match output {
+                Some(path) => {
+                    if let Err(e) = std::fs::write(&path, rendered) {
+                        eprintln!("error: can't write {}: {e}", path.display());
+                        return Ok(exit::CANT_CREAT);
+                    }
+                }
+                None => print!("{rendered}"),
+            }
+        }
+    }
+
+    Ok(0)
+}
+
+/// Retrieves a secret (as a raw string) or a decrypted parameter.
+async fn get(config: &SdkConfig, service: Service, name: &str) -> aws_secrets::Result<String> {
+    match service {
+        Service::Sm => name.get_secret_string(config).await,
+        Service::Ssm => name.get_secure_string(config).await,
+    }
+}
+
+/// Returns the top-level keys and values of a JSON object, or `None`
+/// if the value is not a JSON object.
+fn as_object(value: &str) -> Option<Map<String, Value>> {
+    match serde_json::from_str(value) {
+        Ok(Value::Object(map)) => Some(map),
+        _ => None,
+    }
+}
+
+/// Renders a JSON value as plain text, without quotes around strings.
+fn to_plain(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Converts a secret or parameter name into a valid environment variable name.
+fn env_name(name: &str) -> String {
+    let name = name.rsplit('/').next().unwrap_or(name);
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Quotes a value for use in a POSIX shell.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+fn print_values(format: Format, values: &[(String, String)]) {
+    match format {
+        Format::Plain => values.iter().for_each(|(_, value)| println!("{value}")),
+        Format::Json => {
+            let map: Map<String, Value> = values
+                .iter()
+                .map(|(name, value)| {
+                    let value = serde_json::from_str(value)
+                        .unwrap_or_else(|_| Value::String(value.clone()));
+                    (name.clone(), value)
+                })
+                .collect();
+            let out = if map.len() == 1 {
+                map.into_iter().next().unwrap().1
+            } else {
+                Value::Object(map)
+            };
+            println!("{out}");
+        }
+        Format::Env => {
+            for (name, value) in values {
+                match as_object(value) {
+                    Some(map) => {
+                        for (key, value) in map {
+                            println!("{}={}", env_name(&key), shell_quote(&to_plain(&value)));
+                        }
+                    }
+                    None => println!("{}={}", env_name(name), shell_quote(value)),
+                }
+            }
+        }
+    }
+}
+
+/// Compares two values key-by-key (for JSON objects) or as a whole,
+/// returning one line per difference. Only key names are reported, so
+/// that secret values are never written to the terminal.
+fn diff(left: &str, right: &str) -> Vec<String> {
+    match (as_object(left), as_object(right)) {
+        (Some(left), Some(right)) => {
+            let mut keys: BTreeMap<&String, (Option<&Value>, Option<&Value>)> = BTreeMap::new();
+            for (key, value) in &left {
+                keys.entry(key).or_default().0 = Some(value);
+            }
+            for (key, value) in &right {
+                keys.entry(key).or_default().1 = Some(value);
+            }
+            keys.into_iter()
+                .filter_map(|(key, values)| match values {
+                    (Some(_), None) => Some(format!("- {key}")),
+                    (None, Some(_)) => Some(format!("+ {key}")),
+                    (Some(l), Some(r)) if l != r => Some(format!("~ {key}")),
+                    _ => None,
+                })
+                .collect()
+        }
+        _ if left != right => vec!["~ <value>".to_owned()],
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[test]
+    fn maps_errors_to_exit_codes() {
+        let timeout = || Error::Timeout {
+            resource: Some("prod/db".to_owned()),
+            source: Box::new(Error::Io(io::ErrorKind::TimedOut.into())),
+        };
+        let missing_field = Error::MissingField {
+            secret_name: "prod/db".to_owned(),
+            field: "password".to_owned(),
+        };
+
+        assert_eq!(exit_code(&timeout()), exit::UNAVAILABLE);
+        assert_eq!(
+            exit_code(&Error::Coalesced(Arc::new(timeout()))),
+            exit::UNAVAILABLE
+        );
+        assert_eq!(exit_code(&missing_field), exit::DATA_ERR);
+        assert_eq!(
+            exit_code(&Error::Io(io::ErrorKind::NotFound.into())),
+            exit::NO_INPUT
+        );
+    }
+}
@@ -0,0 +1,116 @@
+//! Get notified when a secret changes, for example after rotation.
+//!
+//! [`watch_secret`] polls `secretsmanager:DescribeSecret` on an interval,
+//! and yields a [`SecretChange`] for the current version, then whenever a
+//! new version becomes `AWSCURRENT`. The new value is only retrieved (with
+//! `secretsmanager:GetSecretValue`) when the version actually changed.
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use aws_secrets::changes::watch_secret;
+//! use futures_util::StreamExt;
+//!
+//! # async fn run() {
+//! let config = aws_secrets::config_from_env().await;
+//! let mut changes = Box::pin(watch_secret(config, "prod/db", Duration::from_secs(60)));
+//!
+//! while let Some(change) = changes.next().await {
+//!     match change {
+//!         Ok(change) => println!("now at version {}", change.new_version),
+//!         Err(e) => eprintln!("couldn't check for changes: {e}"),
+//!     }
+//! }
+//! # }
+//! ```
+
+use std::time::Duration;
+
+use aws_config::SdkConfig;
+use futures_util::stream::{self, Stream};
+
+use crate::secretsmanager::get_secret_value;
+use crate::{Result, SecretsExt};
+
+/// A change of the `AWSCURRENT` version of a secret.
+#[derive(Debug, Clone)]
+pub struct SecretChange {
+    /// Name (or ARN) of the secret, as passed to [`watch_secret`].
+    pub secret_id: String,
+    /// Id of the version which was `AWSCURRENT` before the change, or `None`
+    /// for the first item of the stream.
+    pub old_version: Option<String>,
+    /// Id of the version which is now `AWSCURRENT`.
+    pub new_version: String,
+    /// The `SecretString` of the new version.
+    pub value: String,
+}
+
+struct State {
+    config: SdkConfig,
+    secret_id: String,
+    interval: Duration,
+    current_version: Option<String>,
+    started: bool,
+}
+
+/// Returns a stream which yields an item each time the `AWSCURRENT`
+/// version of a secret changes, checking every `interval`.
+///
+/// The first item is the baseline: the version which is current when the
+/// stream is first (successfully) polled, with no `old_version`. Errors are
+/// yielded as they occur, but don't end the stream; the next check happens
+/// after `interval` as usual.
+pub fn watch_secret(
+    config: SdkConfig,
+    secret_id: impl Into<String>,
+    interval: Duration,
+) -> impl Stream<Item = Result<SecretChange>> {
+    let state = State {
+        config,
+        secret_id: secret_id.into(),
+        interval,
+        current_version: None,
+        started: false,
+    };
+
+    stream::unfold(state, |mut state| async move {
+        loop {
+            if state.started {
+                tokio::time::sleep(state.interval).await;
+            }
+            state.started = true;
+
+            let version_id = match current_version_id(&state).await {
+                Ok(Some(id)) if state.current_version.as_ref() != Some(&id) => id,
+                Ok(_) => continue,
+                Err(e) => return Some((Err(e), state)),
+            };
+
+            // Retrieve the value by version id, in case the stage moves again meanwhile.
+            let value =
+                get_secret_value(&state.config, &state.secret_id, None, Some(&version_id)).await;
+            return match value {
+                Ok(value) => {
+                    let change = SecretChange {
+                        secret_id: state.secret_id.clone(),
+                        old_version: state.current_version.replace(version_id.clone()),
+                        new_version: version_id,
+                        value: value.secret_string,
+                    };
+                    Some((Ok(change), state))
+                }
+                Err(e) => Some((Err(e), state)),
+            };
+        }
+    })
+}
+
+/// Returns the id of the `AWSCURRENT` version of the secret.
+async fn current_version_id(state: &State) -> Result<Option<String>> {
+    let description = state
+        .secret_id
+        .as_str()
+        .describe_secret(&state.config)
+        .await?;
+    Ok(description.current_version_id().map(str::to_owned))
+}
@@ -0,0 +1,292 @@
+//! Coalesce concurrent lookups of the same secret or parameter.
+//!
+//! On a cold start, many tasks may look up the same secret at once. A
+//! [`CoalescingStore`] wraps another [`SecretStore`], so that concurrent
+//! lookups of the same secret (or parameter) share a single in-flight
+//! request: the first lookup sends it, and the others wait for its result.
+//! Values are not cached: once the request completes, the next lookup sends
+//! a new one. Writes are passed through as is.
+//!
+//! When a request fails, each of its callers receives an [`Error::Coalesced`]
+//! which wraps the same error, whether or not the request was actually
+//! shared. Methods such as [`Error::is_not_found`] look through it.
+//!
+//! ```no_run
+//! use aws_secrets::coalesce::CoalescingStore;
+//! use aws_secrets::store::{AwsStore, SecretStore};
+//!
+//! # async fn run() -> aws_secrets::Result<()> {
+//! let store = CoalescingStore::new(AwsStore::new(aws_secrets::config_from_env().await));
+//!
+//! // A single `GetSecretValue` request is sent for all these lookups.
+//! let lookups = (0..200).map(|_| store.get_secret_string("prod/db"));
+//! let passwords = futures_util::future::try_join_all(lookups).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use futures_util::future::{BoxFuture, FutureExt, Shared};
+
+use crate::store::SecretStore;
+#[cfg(any(feature = "tracing", feature = "metrics"))]
+use crate::telemetry;
+use crate::{Error, Result};
+
+/// A request shared by concurrent lookups.
+type SharedLookup = Shared<BoxFuture<'static, std::result::Result<String, Arc<Error>>>>;
+
+/// Identifies a lookup, so that only identical lookups are coalesced.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Key {
+    Secret(String),
+    Parameter { name: String, with_decryption: bool },
+}
+
+impl Key {
+    /// Records whether a lookup joined an in-flight request, or sent one.
+    #[cfg(any(feature = "tracing", feature = "metrics"))]
+    fn record(&self, shared: bool) {
+        let operation = match self {
+            Self::Secret(_) => "GetSecretValue",
+            Self::Parameter { .. } => "GetParameter",
+        };
+
+        #[cfg(feature = "tracing")]
+        telemetry::trace_coalesced(operation, self.name(), shared);
+
+        #[cfg(feature = "metrics")]
+        telemetry::count_coalesced(operation, shared);
+    }
+
+    #[cfg(feature = "tracing")]
+    fn name(&self) -> &str {
+        match self {
+            Self::Secret(name) | Self::Parameter { name, .. } => name,
+        }
+    }
+}
+
+/// A [`SecretStore`] which coalesces concurrent lookups; see the
+/// [module docs](self).
+///
+/// Cloning a `CoalescingStore` is cheap, and the clones share their
+/// in-flight requests.
+pub struct CoalescingStore<S: ?Sized> {
+    inner: Arc<S>,
+    in_flight: Arc<Mutex<HashMap<Key, SharedLookup>>>,
+}
+
+impl<S: SecretStore + 'static> CoalescingStore<S> {
+    /// Wraps a store.
+    pub fn new(inner: S) -> Self {
+        Self::from_arc(Arc::new(inner))
+    }
+}
+
+impl<S: SecretStore + ?Sized + 'static> CoalescingStore<S> {
+    /// Wraps a shared store, such as an `Arc<dyn SecretStore>`.
+    pub fn from_arc(inner: Arc<S>) -> Self {
+        Self {
+            inner,
+            in_flight: Arc::default(),
+        }
+    }
+
+    /// Returns the wrapped store.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Joins the in-flight request for `key`, or sends one with `lookup`.
+    async fn lookup<F, Fut>(&self, key: Key, lookup: F) -> Result<String>
+    where
+        F: FnOnce(Arc<S>) -> Fut,
+        Fut: Future<Output = Result<String>> + Send + 'static,
+    {
+        let request = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            match in_flight.get(&key) {
+                Some(request) => {
+                    #[cfg(any(feature = "tracing", feature = "metrics"))]
+                    key.record(true);
+                    request.clone()
+                }
+                None => {
+                    let request = lookup(Arc::clone(&self.inner));
+                    let in_flight_ref = Arc::clone(&self.in_flight);
+                    let done = key.clone();
+                    let request = async move {
+                        let result = request.await.map_err(Arc::new);
+                        in_flight_ref.lock().unwrap().remove(&done);
+                        result
+                    }
+                    .boxed()
+                    .shared();
+                    #[cfg(any(feature = "tracing", feature = "metrics"))]
+                    key.record(false);
+                    in_flight.insert(key, request.clone());
+                    request
+                }
+            }
+        };
+
+        // Other callers may still hold the error, so it is always wrapped,
+        // rather than only when it happens to be shared.
+        request.await.map_err(Error::Coalesced)
+    }
+}
+
+impl<S: ?Sized> Clone for CoalescingStore<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+            in_flight: Arc::clone(&self.in_flight),
+        }
+    }
+}
+
+impl<S: ?Sized> fmt::Debug for CoalescingStore<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let in_flight = self.in_flight.lock().unwrap();
+        f.debug_struct("CoalescingStore")
+            .field("in_flight", &in_flight.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+#[async_trait]
+impl<S: SecretStore + ?Sized + 'static> SecretStore for CoalescingStore<S> {
+    async fn get_secret_string(&self, secret_id: &str) -> Result<String> {
+        let owned = secret_id.to_owned();
+        self.lookup(Key::Secret(owned.clone()), move |inner| async move {
+            inner.get_secret_string(&owned).await
+        })
+        .await
+    }
+
+    async fn put_secret_string(&self, secret_id: &str, value: &str) -> Result<()> {
+        self.inner.put_secret_string(secret_id, value).await
+    }
+
+    async fn get_parameter(&self, name: &str, with_decryption: bool) -> Result<String> {
+        let owned = name.to_owned();
+        let key = Key::Parameter {
+            name: owned.clone(),
+            with_decryption,
+        };
+        self.lookup(key, move |inner| async move {
+            inner.get_parameter(&owned, with_decryption).await
+        })
+        .await
+    }
+
+    async fn put_parameter(&self, name: &str, value: &str, secure: bool) -> Result<()> {
+        self.inner.put_parameter(name, value, secure).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MemoryStore;
+    use futures_util::future::join_all;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A store which counts its lookups, and yields before answering them,
+    /// so that concurrent lookups overlap.
+    #[derive(Debug)]
+    struct CountingStore {
+        lookups: AtomicUsize,
+        store: MemoryStore,
+    }
+
+    #[async_trait]
+    impl SecretStore for CountingStore {
+        async fn get_secret_string(&self, secret_id: &str) -> Result<String> {
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            tokio::task::yield_now().await;
+            self.store.get_secret_string(secret_id).await
+        }
+
+        async fn put_secret_string(&self, secret_id: &str, value: &str) -> Result<()> {
+            self.store.put_secret_string(secret_id, value).await
+        }
+
+        async fn get_parameter(&self, name: &str, with_decryption: bool) -> Result<String> {
+            self.lookups.fetch_add(1, Ordering::SeqCst);
+            tokio::task::yield_now().await;
+            self.store.get_parameter(name, with_decryption).await
+        }
+
+        async fn put_parameter(&self, name: &str, value: &str, secure: bool) -> Result<()> {
+            self.store.put_parameter(name, value, secure).await
+        }
+    }
+
+    fn store() -> CoalescingStore<CountingStore> {
+        CoalescingStore::new(CountingStore {
+            lookups: AtomicUsize::new(0),
+            store: MemoryStore::new().with_secret("prod/db", "hunter2"),
+        })
+    }
+
+    #[tokio::test]
+    async fn concurrent_lookups_share_one_request() {
+        let store = store();
+
+        let values = join_all((0..10).map(|_| store.get_secret_string("prod/db"))).await;
+
+        assert!(values.iter().all(|v| v.as_deref().ok() == Some("hunter2")));
+        assert_eq!(store.inner().lookups.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn shared_errors_are_cloned() {
+        let store = store();
+
+        let errors = join_all((0..2).map(|_| store.get_parameter("/missing", true))).await;
+
+        for err in errors {
+            let err = err.unwrap_err();
+            assert!(err.is_not_found());
+            assert!(matches!(err, Error::Coalesced(_)));
+        }
+        assert_eq!(store.inner().lookups.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn shared_errors_are_wrapped_after_other_callers_drop_theirs() {
+        let store = store();
+        let mut first = Box::pin(store.get_parameter("/missing", true));
+        let mut second = Box::pin(store.get_parameter("/missing", true));
+        // The first lookup sends the request, and the second one joins it.
+        assert!((&mut first).now_or_never().is_none());
+        assert!((&mut second).now_or_never().is_none());
+
+        drop(first.await.unwrap_err());
+        let err = second.await.unwrap_err();
+
+        assert!(err.is_not_found());
+        assert!(matches!(err, Error::Coalesced(_)));
+        assert_eq!(store.inner().lookups.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn sequential_lookups_are_not_cached() {
+        let store = store();
+
+        let err = store.get_parameter("/missing", true).await.unwrap_err();
+        assert!(err.is_not_found());
+        assert!(matches!(err, Error::Coalesced(_)));
+        store.get_secret_string("prod/db").await.unwrap();
+        store.get_secret_string("prod/db").await.unwrap();
+
+        assert_eq!(store.inner().lookups.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,322 @@
+use std::env;
+use std::time::Duration;
+
+use aws_config::retry::RetryConfig;
+use aws_config::timeout::TimeoutConfig;
+use aws_config::SdkConfig;
+use aws_credential_types::Credentials;
+use aws_types::region::Region;
+
+/// Environment variable which points both services at a local endpoint, with
+/// test credentials, e.g. `http://localhost:4566` to use LocalStack.
+pub const ENDPOINT_URL_ENV_VAR: &str = "AWS_SECRETS_ENDPOINT_URL";
+
+/// Builds the shared `SdkConfig` consumed by [`SecretsExt`] and
+/// [`SSMParamExt`] methods, without depending on `aws-config` directly.
+///
+/// Settings which are not set are loaded from the environment, as with
+/// [`config_from_env`](crate::config_from_env). In particular, unless an
+/// endpoint URL is set, the [`ENDPOINT_URL_ENV_VAR`] variable is used as a
+/// [`local_endpoint`](Self::local_endpoint).
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use aws_secrets::ConfigBuilder;
+///
+/// # async fn run() {
+/// let config = ConfigBuilder::new()
+///     .region("eu-west-1")
+///     .profile("prod")
+///     .operation_timeout(Duration::from_secs(5))
+///     .max_attempts(5)
+///     .load()
+///     .await;
+/// # }
+/// ```
+///
+/// [`SecretsExt`]: crate::SecretsExt
+/// [`SSMParamExt`]: crate::SSMParamExt
+#[derive(Debug, Clone, Default)]
+pub struct ConfigBuilder {
+    region: Option<String>,
+    profile: Option<String>,
+    endpoint_url: Option<String>,
+    connect_timeout: Option<Duration>,
+    read_timeout: Option<Duration>,
+    operation_timeout: Option<Duration>,
+    operation_attempt_timeout: Option<Duration>,
+    max_attempts: Option<u32>,
+    initial_backoff: Option<Duration>,
+    use_fips: Option<bool>,
+    use_dual_stack: Option<bool>,
+    test_credentials: bool,
+}
+
+impl ConfigBuilder {
+    /// Creates a builder which loads all settings from the environment.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the region, e.g. `us-east-1`.
+    pub fn region(mut self, region: impl Into<String>) -> Self {
+        self.region = Some(region.into());
+        self
+    }
+
+    /// Sets the profile to load credentials and settings from, in
+    /// `~/.aws/config` and `~/.aws/credentials`.
+    pub fn profile(mut self, profile: impl Into<String>) -> Self {
+        self.profile = Some(profile.into());
+        self
+    }
+
+    /// Overrides the endpoint URL of both services, e.g. to use a VPC
+    /// endpoint or a local emulator.
+    pub fn endpoint_url(mut self, url: impl Into<String>) -> Self {
+        self.endpoint_url = Some(url.into());
+        self
+    }
+
+    /// Points both services at a local stand-in, such as LocalStack or moto
+    /// (e.g. `http://localhost:4566`), with static test credentials.
+    ///
+    /// The region defaults to `us-east-1`, unless set otherwise.
+    pub fn local_endpoint(mut self, url: impl Into<String>) -> Self {
+        self.endpoint_url = Some(url.into());
+        self.test_credentials = true;
+        self
+    }
+
+    /// Sets the timeout for establishing a connection.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the timeout for reading the first byte of a response.
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the timeout for a whole operation, including retries.
+    pub fn operation_timeout(mut self, timeout: Duration) -> Self {
+        self.operation_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the timeout for each attempt of an operation.
+    pub fn operation_attempt_timeout(mut self, timeout: Duration) -> Self {
+        self.operation_attempt_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the maximum number of attempts per operation (including the
+    /// first one); `1` disables retries.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = Some(max_attempts);
+        self
+    }
+
+    /// Sets the backoff before the first retry, which grows exponentially
+    /// for later retries.
+    pub fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = Some(backoff);
+        self
+    }
+
+    /// Sets whether to use FIPS-compliant endpoints.
+    pub fn fips(mut self, use_fips: bool) -> Self {
+        self.use_fips = Some(use_fips);
+        self
+    }
+
+    /// Sets whether to use dual-stack (IPv4 and IPv6) endpoints.
+    pub fn dual_stack(mut self, use_dual_stack: bool) -> Self {
+        self.use_dual_stack = Some(use_dual_stack);
+        self
+    }
+
+    /// Loads the config, filling in unset settings from the environment.
+    pub async fn load(self) -> SdkConfig {
+        self.with_env_endpoint(env::var(ENDPOINT_URL_ENV_VAR).ok())
+            .load_settings()
+            .await
+    }
+
+    /// Uses the endpoint URL from [`ENDPOINT_URL_ENV_VAR`], if any, as a
+    /// local endpoint, unless an endpoint URL is already set.
+    fn with_env_endpoint(self, url: Option<String>) -> Self {
+        match url {
+            Some(url) if self.endpoint_url.is_none() => self.local_endpoint(url),
+            _ => self,
+        }
+    }
+
+    async fn load_settings(self) -> SdkConfig {
+        let mut loader = aws_config::from_env();
+
+        let region = match self.region {
+            Some(region) => Some(region),
+            None if self.test_credentials => Some("us-east-1".to_owned()),
+            None => None,
+        };
+        if let Some(region) = region {
+            loader = loader.region(Region::new(region));
+        }
+        if let Some(profile) = &self.profile {
+            loader = loader.profile_name(profile);
+        }
+        if let Some(url) = self.endpoint_url {
+            loader = loader.endpoint_url(url);
+        }
+        if self.test_credentials {
+            loader = loader.credentials_provider(Credentials::new(
+                "test",
+                "test",
+                None,
+                None,
+                "aws-secrets-local",
+            ));
+        }
+        if let Some(timeout_config) = self.timeout_config() {
+            loader = loader.timeout_config(timeout_config);
+        }
+        if let Some(retry_config) = self.retry_config() {
+            loader = loader.retry_config(retry_config);
+        }
+        if let Some(use_fips) = self.use_fips {
+            loader = loader.use_fips(use_fips);
+        }
+        if let Some(use_dual_stack) = self.use_dual_stack {
+            loader = loader.use_dual_stack(use_dual_stack);
+        }
+
+        loader.load().await
+    }
+
+    fn timeout_config(&self) -> Option<TimeoutConfig> {
+        if self.connect_timeout.is_none()
+            && self.read_timeout.is_none()
+            && self.operation_timeout.is_none()
+            && self.operation_attempt_timeout.is_none()
+        {
+            return None;
+        }
+
+        let mut builder = TimeoutConfig::builder();
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.read_timeout {
+            builder = builder.read_timeout(timeout);
+        }
+        if let Some(timeout) = self.operation_timeout {
+            builder = builder.operation_timeout(timeout);
+        }
+        if let Some(timeout) = self.operation_attempt_timeout {
+            builder = builder.operation_attempt_timeout(timeout);
+        }
+        Some(builder.build())
+    }
+
+    fn retry_config(&self) -> Option<RetryConfig> {
+        if self.max_attempts.is_none() && self.initial_backoff.is_none() {
+            return None;
+        }
+
+        let mut retry_config = RetryConfig::standard();
+        if let Some(max_attempts) = self.max_attempts {
+            retry_config = retry_config.with_max_attempts(max_attempts);
+        }
+        if let Some(backoff) = self.initial_backoff {
+            retry_config = retry_config.with_initial_backoff(backoff);
+        }
+        Some(retry_config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_credential_types::provider::ProvideCredentials;
+
+    #[tokio::test]
+    async fn applies_settings() {
+        let config = ConfigBuilder::new()
+            .region("eu-west-1")
+            .endpoint_url("http://localhost:4566")
+            .operation_timeout(Duration::from_secs(5))
+            .max_attempts(5)
+            .fips(true)
+            .load()
+            .await;
+
+        assert_eq!(config.region().map(|r| r.as_ref()), Some("eu-west-1"));
+        assert_eq!(config.endpoint_url(), Some("http://localhost:4566"));
+        assert_eq!(
+            config.timeout_config().and_then(|t| t.operation_timeout()),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(config.retry_config().map(|r| r.max_attempts()), Some(5));
+        assert_eq!(config.use_fips(), Some(true));
+    }
+
+    #[tokio::test]
+    async fn local_endpoint_uses_test_credentials() {
+        let config = ConfigBuilder::new()
+            .local_endpoint("http://localhost:4566")
+            .load()
+            .await;
+
+        assert_eq!(config.region().map(|r| r.as_ref()), Some("us-east-1"));
+        assert_eq!(config.endpoint_url(), Some("http://localhost:4566"));
+        let credentials = config
+            .credentials_provider()
+            .unwrap()
+            .provide_credentials()
+            .await
+            .unwrap();
+        assert_eq!(credentials.access_key_id(), "test");
+    }
+
+    #[tokio::test]
+    async fn env_endpoint_uses_test_credentials() {
+        let config = ConfigBuilder::new()
+            .with_env_endpoint(Some("http://localhost:4566".to_owned()))
+            .load_settings()
+            .await;
+
+        assert_eq!(config.region().map(|r| r.as_ref()), Some("us-east-1"));
+        assert_eq!(config.endpoint_url(), Some("http://localhost:4566"));
+        let credentials = config
+            .credentials_provider()
+            .unwrap()
+            .provide_credentials()
+            .await
+            .unwrap();
+        assert_eq!(credentials.access_key_id(), "test");
+    }
+
+    #[test]
+    fn env_endpoint_does_not_override_endpoint_url() {
+        let builder = ConfigBuilder::new()
+            .endpoint_url("https://vpce.example.com")
+            .with_env_endpoint(Some("http://localhost:4566".to_owned()));
+
+        assert_eq!(
+            builder.endpoint_url.as_deref(),
+            Some("https://vpce.example.com")
+        );
+        assert!(!builder.test_credentials);
+    }
+
+    #[test]
+    fn leaves_unset_settings_to_the_environment() {
+        let builder = ConfigBuilder::new().region("eu-west-1");
+
+        assert!(builder.timeout_config().is_none());
+        assert!(builder.retry_config().is_none());
+    }
+}
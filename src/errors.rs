@@ -2,7 +2,13 @@ use thiserror::Error;
 
 #[cfg(feature = "sm")]
 mod sm_imports {
-    pub(crate) use aws_sdk_secretsmanager::error::{GetSecretValueError, TagResourceError};
+    pub(crate) use aws_sdk_secretsmanager::error::{
+        CancelRotateSecretError, DeleteResourcePolicyError, DescribeSecretError,
+        GetResourcePolicyError, GetSecretValueError, ListSecretVersionIdsError, ListSecretsError,
+        PutResourcePolicyError, PutSecretValueError, RemoveRegionsFromReplicationError,
+        ReplicateSecretToRegionsError, RotateSecretError, StopReplicationToReplicaError,
+        TagResourceError, UpdateSecretVersionStageError, ValidateResourcePolicyError,
+    };
     pub(crate) use aws_sdk_secretsmanager::types::SdkError as SMError;
 }
 #[cfg(feature = "sm")]
@@ -10,7 +16,11 @@ use sm_imports::*;
 
 #[cfg(feature = "params")]
 mod params_imports {
-    pub(crate) use aws_sdk_ssm::error::GetParameterError;
+    pub(crate) use aws_sdk_ssm::error::{
+        AddTagsToResourceError, DescribeParametersError, GetParameterError,
+        GetParameterHistoryError, GetParametersByPathError, LabelParameterVersionError,
+        PutParameterError, UnlabelParameterVersionError,
+    };
     pub(crate) use aws_sdk_ssm::types::SdkError as ParamsError;
 }
 #[cfg(feature = "params")]
@@ -24,6 +34,10 @@ pub enum Error {
     #[cfg(feature = "sm")]
     #[error("couldn't set tag")]
     SetTag(#[from] SMError<TagResourceError>),
+    /// Raised when an error occurs in the `secretsmanager:ListSecrets` operation
+    #[cfg(feature = "sm")]
+    #[error("couldn't list secrets")]
+    ListSecrets(#[from] SMError<ListSecretsError>),
     /// Indicates a `serde` error when de-serializing a JSON string.
     #[cfg(feature = "sm")]
     #[error("couldn't deserialize secret string")]
@@ -37,6 +51,64 @@ pub enum Error {
         /// Original error
         source: ParamsError<GetParameterError>,
     },
+    /// Raised when an error occurs in the `ssm:GetParametersByPath` operation
+    #[cfg(feature = "params")]
+    #[error("[{path:?}] couldn't read params by path")]
+    ReadParamsByPath {
+        /// Path of the Parameters to retrieve
+        path: String,
+        /// Original error
+        source: ParamsError<GetParametersByPathError>,
+    },
+    /// Raised when an error occurs in the `ssm:GetParameterHistory` operation
+    #[cfg(feature = "params")]
+    #[error("[{param_name:?}] couldn't read param history")]
+    ReadParamHistory {
+        /// Name of the Parameter whose history to retrieve
+        param_name: String,
+        /// Original error
+        source: ParamsError<GetParameterHistoryError>,
+    },
+    /// Raised when an error occurs in the `ssm:LabelParameterVersion` operation
+    #[cfg(feature = "params")]
+    #[error("[{param_name:?}] couldn't label param version")]
+    LabelParam {
+        /// Name of the Parameter to label
+        param_name: String,
+        /// Original error
+        source: ParamsError<LabelParameterVersionError>,
+    },
+    /// Raised when an error occurs in the `ssm:UnlabelParameterVersion` operation
+    #[cfg(feature = "params")]
+    #[error("[{param_name:?}] couldn't unlabel param version")]
+    UnlabelParam {
+        /// Name of the Parameter to unlabel
+        param_name: String,
+        /// Original error
+        source: ParamsError<UnlabelParameterVersionError>,
+    },
+    /// Raised when an error occurs in the `ssm:DescribeParameters` operation
+    #[cfg(feature = "params")]
+    #[error("couldn't describe params")]
+    DescribeParams(#[from] ParamsError<DescribeParametersError>),
+    /// Raised when an error occurs in the `ssm:PutParameter` operation
+    #[cfg(feature = "params")]
+    #[error("[{param_name:?}] couldn't put param")]
+    PutParam {
+        /// Name of the Parameter to create or update
+        param_name: String,
+        /// Original error
+        source: ParamsError<PutParameterError>,
+    },
+    /// Raised when an error occurs in the `ssm:AddTagsToResource` operation
+    #[cfg(feature = "params")]
+    #[error("[{param_name:?}] couldn't set param tag")]
+    SetParamTag {
+        /// Name of the Parameter to tag
+        param_name: String,
+        /// Original error
+        source: ParamsError<AddTagsToResourceError>,
+    },
     /// Raised when an error occurs in the `secretsmanager:GetSecretValue` operation
     #[cfg(feature = "sm")]
     #[error("[{secret_name:?}] couldn't read secret")]
@@ -46,7 +118,446 @@ pub enum Error {
         /// Original error
         source: SMError<GetSecretValueError>,
     },
-    /// Unknown library error (currently unused)
+    /// Raised when an error occurs in the `secretsmanager:PutSecretValue` operation
+    #[cfg(feature = "sm")]
+    #[error("[{secret_name:?}] couldn't put secret")]
+    PutSecret {
+        /// Name of the Secret to update
+        secret_name: String,
+        /// Original error
+        source: SMError<PutSecretValueError>,
+    },
+    /// Raised when an error occurs in the `secretsmanager:DescribeSecret` operation
+    #[cfg(feature = "sm")]
+    #[error("[{secret_name:?}] couldn't describe secret")]
+    DescribeSecret {
+        /// Name of the Secret to describe
+        secret_name: String,
+        /// Original error
+        source: SMError<DescribeSecretError>,
+    },
+    /// Raised when an error occurs in the `secretsmanager:ListSecretVersionIds` operation
+    #[cfg(feature = "sm")]
+    #[error("[{secret_name:?}] couldn't list secret versions")]
+    ListSecretVersions {
+        /// Name of the Secret whose versions to list
+        secret_name: String,
+        /// Original error
+        source: SMError<ListSecretVersionIdsError>,
+    },
+    /// Raised when an error occurs in the `secretsmanager:UpdateSecretVersionStage` operation
+    #[cfg(feature = "sm")]
+    #[error("[{secret_name:?}] couldn't update secret version stage")]
+    UpdateVersionStage {
+        /// Name of the Secret to update
+        secret_name: String,
+        /// Original error
+        source: SMError<UpdateSecretVersionStageError>,
+    },
+    /// Raised when an error occurs in the `secretsmanager:RotateSecret` operation
+    #[cfg(feature = "sm")]
+    #[error("[{secret_name:?}] couldn't rotate secret")]
+    RotateSecret {
+        /// Name of the Secret to rotate
+        secret_name: String,
+        /// Original error
+        source: SMError<RotateSecretError>,
+    },
+    /// Raised when an error occurs in the `secretsmanager:CancelRotateSecret` operation
+    #[cfg(feature = "sm")]
+    #[error("[{secret_name:?}] couldn't cancel secret rotation")]
+    CancelRotateSecret {
+        /// Name of the Secret whose rotation to cancel
+        secret_name: String,
+        /// Original error
+        source: SMError<CancelRotateSecretError>,
+    },
+    /// Raised when an error occurs in the `secretsmanager:GetResourcePolicy` operation
+    #[cfg(feature = "sm")]
+    #[error("[{secret_name:?}] couldn't get resource policy")]
+    GetResourcePolicy {
+        /// Name of the Secret
+        secret_name: String,
+        /// Original error
+        source: SMError<GetResourcePolicyError>,
+    },
+    /// Raised when an error occurs in the `secretsmanager:PutResourcePolicy` operation
+    #[cfg(feature = "sm")]
+    #[error("[{secret_name:?}] couldn't put resource policy")]
+    PutResourcePolicy {
+        /// Name of the Secret
+        secret_name: String,
+        /// Original error
+        source: SMError<PutResourcePolicyError>,
+    },
+    /// Raised when an error occurs in the `secretsmanager:DeleteResourcePolicy` operation
+    #[cfg(feature = "sm")]
+    #[error("[{secret_name:?}] couldn't delete resource policy")]
+    DeleteResourcePolicy {
+        /// Name of the Secret
+        secret_name: String,
+        /// Original error
+        source: SMError<DeleteResourcePolicyError>,
+    },
+    /// Raised when an error occurs in the `secretsmanager:ValidateResourcePolicy` operation
+    #[cfg(feature = "sm")]
+    #[error("[{secret_name:?}] couldn't validate resource policy")]
+    ValidateResourcePolicy {
+        /// Name of the Secret (empty if none was given)
+        secret_name: String,
+        /// Original error
+        source: SMError<ValidateResourcePolicyError>,
+    },
+    /// Raised when an error occurs in the `secretsmanager:ReplicateSecretToRegions` operation
+    #[cfg(feature = "sm")]
+    #[error("[{secret_name:?}] couldn't replicate secret")]
+    ReplicateSecret {
+        /// Name of the Secret to replicate
+        secret_name: String,
+        /// Original error
+        source: SMError<ReplicateSecretToRegionsError>,
+    },
+    /// Raised when an error occurs in the `secretsmanager:RemoveRegionsFromReplication` operation
+    #[cfg(feature = "sm")]
+    #[error("[{secret_name:?}] couldn't remove regions from replication")]
+    RemoveReplicaRegions {
+        /// Name of the primary Secret
+        secret_name: String,
+        /// Original error
+        source: SMError<RemoveRegionsFromReplicationError>,
+    },
+    /// Raised when an error occurs in the `secretsmanager:StopReplicationToReplica` operation
+    #[cfg(feature = "sm")]
+    #[error("[{secret_name:?}] couldn't stop replication")]
+    StopReplication {
+        /// Name of the replica Secret
+        secret_name: String,
+        /// Original error
+        source: SMError<StopReplicationToReplicaError>,
+    },
+    /// Raised when a step of a secret rotation fails
+    #[cfg(feature = "rotation")]
+    #[error("[{secret_name:?}] rotation step {step} failed")]
+    Rotation {
+        /// Name of the Secret being rotated
+        secret_name: String,
+        /// The rotation step, e.g. `createSecret`
+        step: String,
+        /// Original error
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// Raised when a login failed with every version of a secret
+    #[cfg(feature = "sm")]
+    #[error("[{secret_name:?}] login failed with every version of the secret (tried {stages:?})")]
+    CredentialsRejected {
+        /// Name of the Secret
+        secret_name: String,
+        /// Staging labels of the versions which were tried, in order
+        stages: Vec<String>,
+        /// Error returned by the last login attempt, if any
+        source: Option<Box<dyn std::error::Error + Send + Sync>>,
+    },
+    /// Raised when an operation times out, whether connecting, reading the
+    /// response, or in total; see the [`timeout`](crate::timeout) module
+    #[cfg(any(feature = "sm", feature = "params"))]
+    #[error("{}timed out", .resource.as_ref().map(|r| format!("[{r:?}] ")).unwrap_or_default())]
+    Timeout {
+        /// Name (or path) of the Secret or Parameter, unless the operation
+        /// lists several of them, e.g. `ListSecrets`
+        resource: Option<String>,
+        /// The error returned by the operation
+        source: Box<Error>,
+    },
+    /// Raised when a lookup which may be shared by concurrent callers failed,
+    /// e.g. in a `CoalescingStore`; each caller receives the same error
+    #[cfg(all(feature = "sm", feature = "params"))]
+    #[error(transparent)]
+    Coalesced(std::sync::Arc<Error>),
+    /// Raised when a JSON secret does not contain the requested field
+    #[cfg(feature = "sm")]
+    #[error("[{secret_name:?}] secret has no field {field:?}")]
+    MissingField {
+        /// Name of the Secret that was retrieved
+        secret_name: String,
+        /// Name of the missing field
+        field: String,
+    },
+    /// Raised when a secret has no `SecretString`, e.g. because it is stored
+    /// as `SecretBinary`
+    #[cfg(feature = "sm")]
+    #[error("[{secret_name:?}] secret has no string value")]
+    MissingSecretString {
+        /// Name of the Secret that was retrieved
+        secret_name: String,
+    },
+    /// Raised when a secret or parameter (or a version of it) doesn't exist,
+    /// e.g. in a `MemoryStore`
+    #[cfg(any(feature = "sm", feature = "params"))]
+    #[error("[{name:?}] not found")]
+    NotFound {
+        /// Name of the Secret or Parameter
+        name: String,
+    },
+    /// Raised when a string can't be parsed as a `SecretRef`
+    #[cfg(all(feature = "sm", feature = "params"))]
+    #[error("invalid secret reference {reference:?}: {message}")]
+    InvalidRef {
+        /// The reference which couldn't be parsed
+        reference: String,
+        /// Description of the problem
+        message: String,
+    },
+    /// Raised when a template contains an invalid placeholder
+    #[cfg(feature = "template")]
+    #[error("invalid template (line {line}): {message}")]
+    Template {
+        /// Line number (starting at 1) of the invalid placeholder
+        line: usize,
+        /// Description of the problem
+        message: String,
+    },
+    /// Indicates an I/O error, for example when reading a template file.
+    #[error("I/O error")]
+    Io(#[from] std::io::Error),
+    /// Unknown library error, e.g. when a `RegionFailover` has no regions
     #[error("unknown error")]
     Unknown,
 }
+
+/// Returns the AWS error code of an `SdkError`, if it is a service error.
+#[cfg(any(feature = "sm", feature = "params"))]
+macro_rules! service_code {
+    ($sdk_error:ident, $source:expr) => {
+        match $source {
+            $sdk_error::ServiceError { err, .. } => err.code(),
+            _ => None,
+        }
+    };
+}
+
+/// Returns true if an `SdkError` is a network error, a timeout, or a
+/// server-side (5xx) error.
+#[cfg(any(feature = "sm", feature = "params"))]
+macro_rules! is_transient_failure {
+    ($sdk_error:ident, $source:expr) => {
+        match $source {
+            $sdk_error::TimeoutError(_)
+            | $sdk_error::DispatchFailure(_)
+            | $sdk_error::ResponseError { .. } => true,
+            $sdk_error::ServiceError { raw, .. } => raw.http().status().is_server_error(),
+            _ => false,
+        }
+    };
+}
+
+/// Returns true if an `SdkError` is a timeout: either the operation (or an
+/// attempt) took too long, or connecting or reading the response did.
+#[cfg(any(feature = "sm", feature = "params"))]
+macro_rules! is_timeout_failure {
+    ($sdk_error:ident, $source:expr) => {
+        match $source {
+            $sdk_error::TimeoutError(_) => true,
+            $sdk_error::DispatchFailure(e) => e.is_timeout(),
+            _ => false,
+        }
+    };
+}
+
+impl Error {
+    /// Returns the AWS error code (for example `ResourceNotFoundException`
+    /// or `ParameterNotFound`) when the error was returned by the service.
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            #[cfg(feature = "sm")]
+            Self::SetTag(source) => service_code!(SMError, source),
+            #[cfg(feature = "sm")]
+            Self::ListSecrets(source) => service_code!(SMError, source),
+            #[cfg(feature = "sm")]
+            Self::ReadSecret { source, .. } => service_code!(SMError, source),
+            #[cfg(feature = "sm")]
+            Self::PutSecret { source, .. } => service_code!(SMError, source),
+            #[cfg(feature = "sm")]
+            Self::DescribeSecret { source, .. } => service_code!(SMError, source),
+            #[cfg(feature = "sm")]
+            Self::ListSecretVersions { source, .. } => service_code!(SMError, source),
+            #[cfg(feature = "sm")]
+            Self::UpdateVersionStage { source, .. } => service_code!(SMError, source),
+            #[cfg(feature = "sm")]
+            Self::RotateSecret { source, .. } => service_code!(SMError, source),
+            #[cfg(feature = "sm")]
+            Self::CancelRotateSecret { source, .. } => service_code!(SMError, source),
+            #[cfg(feature = "sm")]
+            Self::GetResourcePolicy { source, .. } => service_code!(SMError, source),
+            #[cfg(feature = "sm")]
+            Self::PutResourcePolicy { source, .. } => service_code!(SMError, source),
+            #[cfg(feature = "sm")]
+            Self::DeleteResourcePolicy { source, .. } => service_code!(SMError, source),
+            #[cfg(feature = "sm")]
+            Self::ValidateResourcePolicy { source, .. } => service_code!(SMError, source),
+            #[cfg(feature = "sm")]
+            Self::ReplicateSecret { source, .. } => service_code!(SMError, source),
+            #[cfg(feature = "sm")]
+            Self::RemoveReplicaRegions { source, .. } => service_code!(SMError, source),
+            #[cfg(feature = "sm")]
+            Self::StopReplication { source, .. } => service_code!(SMError, source),
+            #[cfg(feature = "params")]
+            Self::ReadParam { source, .. } => service_code!(ParamsError, source),
+            #[cfg(feature = "params")]
+            Self::ReadParamsByPath { source, .. } => service_code!(ParamsError, source),
+            #[cfg(feature = "params")]
+            Self::ReadParamHistory { source, .. } => service_code!(ParamsError, source),
+            #[cfg(feature = "params")]
+            Self::LabelParam { source, .. } => service_code!(ParamsError, source),
+            #[cfg(feature = "params")]
+            Self::UnlabelParam { source, .. } => service_code!(ParamsError, source),
+            #[cfg(feature = "params")]
+            Self::DescribeParams(source) => service_code!(ParamsError, source),
+            #[cfg(feature = "params")]
+            Self::PutParam { source, .. } => service_code!(ParamsError, source),
+            #[cfg(feature = "params")]
+            Self::SetParamTag { source, .. } => service_code!(ParamsError, source),
+            #[cfg(all(feature = "sm", feature = "params"))]
+            Self::Coalesced(source) => source.code(),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the operation timed out.
+    pub fn is_timeout(&self) -> bool {
+        #[cfg(all(feature = "sm", feature = "params"))]
+        if let Self::Coalesced(source) = self {
+            return source.is_timeout();
+        }
+        #[cfg(any(feature = "sm", feature = "params"))]
+        if let Self::Timeout { .. } = self {
+            return true;
+        }
+        false
+    }
+
+    /// Wraps the error in [`Error::Timeout`] if the operation timed out.
+    #[cfg(any(feature = "sm", feature = "params"))]
+    pub(crate) fn or_timeout(self, resource: Option<&str>) -> Self {
+        if self.is_sdk_timeout() {
+            Self::Timeout {
+                resource: resource.map(str::to_owned),
+                source: Box::new(self),
+            }
+        } else {
+            self
+        }
+    }
+
+    #[cfg(any(feature = "sm", feature = "params"))]
+    fn is_sdk_timeout(&self) -> bool {
+        match self {
+            #[cfg(feature = "sm")]
+            Self::SetTag(source) => is_timeout_failure!(SMError, source),
+            #[cfg(feature = "sm")]
+            Self::ListSecrets(source) => is_timeout_failure!(SMError, source),
+            #[cfg(feature = "sm")]
+            Self::ReadSecret { source, .. } => is_timeout_failure!(SMError, source),
+            #[cfg(feature = "sm")]
+            Self::PutSecret { source, .. } => is_timeout_failure!(SMError, source),
+            #[cfg(feature = "sm")]
+            Self::DescribeSecret { source, .. } => is_timeout_failure!(SMError, source),
+            #[cfg(feature = "sm")]
+            Self::ListSecretVersions { source, .. } => is_timeout_failure!(SMError, source),
+            #[cfg(feature = "sm")]
+            Self::UpdateVersionStage { source, .. } => is_timeout_failure!(SMError, source),
+            #[cfg(feature = "sm")]
+            Self::RotateSecret { source, .. } => is_timeout_failure!(SMError, source),
+            #[cfg(feature = "sm")]
+            Self::CancelRotateSecret { source, .. } => is_timeout_failure!(SMError, source),
+            #[cfg(feature = "sm")]
+            Self::GetResourcePolicy { source, .. } => is_timeout_failure!(SMError, source),
+            #[cfg(feature = "sm")]
+            Self::PutResourcePolicy { source, .. } => is_timeout_failure!(SMError, source),
+            #[cfg(feature = "sm")]
+            Self::DeleteResourcePolicy { source, .. } => is_timeout_failure!(SMError, source),
+            #[cfg(feature = "sm")]
+            Self::ValidateResourcePolicy { source, .. } => is_timeout_failure!(SMError, source),
+            #[cfg(feature = "sm")]
+            Self::ReplicateSecret { source, .. } => is_timeout_failure!(SMError, source),
+            #[cfg(feature = "sm")]
+            Self::RemoveReplicaRegions { source, .. } => is_timeout_failure!(SMError, source),
+            #[cfg(feature = "sm")]
+            Self::StopReplication { source, .. } => is_timeout_failure!(SMError, source),
+            #[cfg(feature = "params")]
+            Self::ReadParam { source, .. } => is_timeout_failure!(ParamsError, source),
+            #[cfg(feature = "params")]
+            Self::ReadParamsByPath { source, .. } => is_timeout_failure!(ParamsError, source),
+            #[cfg(feature = "params")]
+            Self::ReadParamHistory { source, .. } => is_timeout_failure!(ParamsError, source),
+            #[cfg(feature = "params")]
+            Self::LabelParam { source, .. } => is_timeout_failure!(ParamsError, source),
+            #[cfg(feature = "params")]
+            Self::UnlabelParam { source, .. } => is_timeout_failure!(ParamsError, source),
+            #[cfg(feature = "params")]
+            Self::DescribeParams(source) => is_timeout_failure!(ParamsError, source),
+            #[cfg(feature = "params")]
+            Self::PutParam { source, .. } => is_timeout_failure!(ParamsError, source),
+            #[cfg(feature = "params")]
+            Self::SetParamTag { source, .. } => is_timeout_failure!(ParamsError, source),
+            _ => false,
+        }
+    }
+
+    /// Returns true if the requested secret or parameter (or version) does not exist.
+    pub fn is_not_found(&self) -> bool {
+        #[cfg(all(feature = "sm", feature = "params"))]
+        if let Self::Coalesced(source) = self {
+            return source.is_not_found();
+        }
+        #[cfg(any(feature = "sm", feature = "params"))]
+        if let Self::NotFound { .. } = self {
+            return true;
+        }
+        matches!(
+            self.code(),
+            Some("ResourceNotFoundException" | "ParameterNotFound" | "ParameterVersionNotFound")
+        )
+    }
+
+    /// Returns true if the error is likely temporary, or specific to one
+    /// region: a network error or timeout, throttling, or a server-side
+    /// error. Only read operations are classified, apart from timeouts.
+    pub fn is_transient(&self) -> bool {
+        #[cfg(all(feature = "sm", feature = "params"))]
+        if let Self::Coalesced(source) = self {
+            return source.is_transient();
+        }
+        if self.is_timeout() {
+            return true;
+        }
+        if matches!(
+            self.code(),
+            Some(
+                "ThrottlingException"
+                    | "Throttling"
+                    | "TooManyRequestsException"
+                    | "RequestLimitExceeded"
+                    | "InternalServiceError"
+                    | "InternalServerError"
+                    | "ServiceUnavailable"
+            )
+        ) {
+            return true;
+        }
+        match self {
+            #[cfg(feature = "sm")]
+            Self::ReadSecret { source, .. } => is_transient_failure!(SMError, source),
+            #[cfg(feature = "sm")]
+            Self::DescribeSecret { source, .. } => is_transient_failure!(SMError, source),
+            #[cfg(feature = "sm")]
+            Self::ListSecrets(source) => is_transient_failure!(SMError, source),
+            #[cfg(feature = "params")]
+            Self::ReadParam { source, .. } => is_transient_failure!(ParamsError, source),
+            #[cfg(feature = "params")]
+            Self::ReadParamsByPath { source, .. } => is_transient_failure!(ParamsError, source),
+            #[cfg(feature = "params")]
+            Self::ReadParamHistory { source, .. } => is_transient_failure!(ParamsError, source),
+            _ => false,
+        }
+    }
+}
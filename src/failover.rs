@@ -0,0 +1,147 @@
+//! Read secrets and parameters from the first healthy region.
+//!
+//! A [`RegionFailover`] holds an ordered list of regions. Each read is tried
+//! in the first region, then in the next one if it fails with a network
+//! error, a timeout, throttling, or a server-side error (see
+//! [`Error::is_transient`]). Other errors, such as a missing secret or
+//! denied access, are returned immediately. The region which served the
+//! value is reported in the [`Served`] result.
+//!
+//! The secrets (or parameters) must exist in each region, for example
+//! because they are replicated.
+//!
+//! ```no_run
+//! use aws_secrets::failover::RegionFailover;
+//!
+//! # async fn run() -> aws_secrets::Result<()> {
+//! let config = aws_secrets::config_from_env().await;
+//! let failover = RegionFailover::new(&config, ["us-east-1", "us-west-2"]);
+//!
+//! let password = failover.get_secret_string("prod/db").await?;
+//! println!("read from {}", password.region);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::future::Future;
+
+use aws_config::SdkConfig;
+use aws_types::region::Region;
+
+#[cfg(feature = "params")]
+use crate::params::get_string_with_decryption;
+#[cfg(feature = "sm")]
+use crate::secretsmanager::get_secret_string_version;
+use crate::telemetry::Attempts;
+use crate::{Error, Result};
+
+/// A value, and the region it was read from.
+#[derive(Debug, Clone)]
+pub struct Served<T> {
+    /// The value.
+    pub value: T,
+    /// The region which served the value.
+    pub region: String,
+}
+
+/// Tries reads in each of an ordered list of regions; see the [module docs](self).
+#[derive(Debug, Clone)]
+pub struct RegionFailover {
+    configs: Vec<(String, SdkConfig)>,
+}
+
+impl RegionFailover {
+    /// Creates a failover over the given regions, in order, using `config`
+    /// (with its region replaced) for each one.
+    pub fn new<I, S>(config: &SdkConfig, regions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let configs = regions
+            .into_iter()
+            .map(|region| {
+                let region = region.into();
+                let config = config
+                    .to_builder()
+                    .region(Region::new(region.clone()))
+                    .build();
+                (region, config)
+            })
+            .collect();
+
+        Self { configs }
+    }
+
+    /// Creates a failover over the regions of the given configs, in order.
+    pub fn from_configs(configs: impl IntoIterator<Item = SdkConfig>) -> Self {
+        let configs = configs
+            .into_iter()
+            .map(|config| {
+                let region = config.region().map(ToString::to_string).unwrap_or_default();
+                (region, config)
+            })
+            .collect();
+
+        Self { configs }
+    }
+
+    /// Returns the regions, in the order they are tried.
+    pub fn regions(&self) -> impl Iterator<Item = &str> {
+        self.configs.iter().map(|(region, _)| region.as_str())
+    }
+
+    /// Retrieves the raw `SecretString` of a secret from AWS Secrets Manager.
+    #[cfg(feature = "sm")]
+    pub async fn get_secret_string(&self, secret_id: &str) -> Result<Served<String>> {
+        self.read(|config| get_secret_string_version(config, secret_id, None, None))
+            .await
+    }
+
+    /// Retrieves and de-serializes a secret from AWS Secrets Manager.
+    #[cfg(feature = "sm")]
+    pub async fn get_secret<T: serde::de::DeserializeOwned>(
+        &self,
+        secret_id: &str,
+    ) -> Result<Served<T>> {
+        let served = self.get_secret_string(secret_id).await?;
+        Ok(Served {
+            value: serde_json::from_str(&served.value)?,
+            region: served.region,
+        })
+    }
+
+    /// Retrieves a parameter from SSM Parameter Store, optionally decrypting it.
+    #[cfg(feature = "params")]
+    pub async fn get_parameter(&self, name: &str, with_decryption: bool) -> Result<Served<String>> {
+        self.read(|config| get_string_with_decryption(config, name, with_decryption))
+            .await
+    }
+
+    /// Runs `read` in each region in turn, until it succeeds or fails with
+    /// an error which isn't transient. If all regions fail, the error from
+    /// the last one is returned.
+    pub async fn read<'a, T, F, Fut>(&'a self, mut read: F) -> Result<Served<T>>
+    where
+        F: FnMut(&'a SdkConfig) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let attempts = Attempts::start("RegionFailover");
+        let mut last_error = None;
+
+        for (attempt, (region, config)) in self.configs.iter().enumerate() {
+            match attempts.run(attempt + 1, read(config)).await {
+                Ok(value) => {
+                    return Ok(Served {
+                        value,
+                        region: region.clone(),
+                    })
+                }
+                Err(e) if e.is_transient() => last_error = Some(e),
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(last_error.unwrap_or(Error::Unknown))
+    }
+}
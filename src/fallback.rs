@@ -0,0 +1,160 @@
+//! Fall back across secret versions while a rotation is in progress.
+//!
+//! During a rotation window, the credentials stored as `AWSCURRENT` may
+//! briefly not match the ones the database (or other service) accepts.
+//! [`CredentialFallback`] tries a caller-supplied login with `AWSCURRENT`
+//! first, then with `AWSPENDING` and `AWSPREVIOUS`, and returns the first
+//! one which works.
+//!
+//! ```no_run
+//! use aws_secrets::fallback::CredentialFallback;
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct DbCredentials {
+//!     username: String,
+//!     password: String,
+//! }
+//!
+//! # async fn connect(_: &str, _: &str) -> Result<(), std::io::Error> { Ok(()) }
+//! # async fn run() -> aws_secrets::Result<()> {
+//! let config = aws_secrets::config_from_env().await;
+//!
+//! let login = CredentialFallback::new(&config, "prod/db")
+//!     .try_login(|creds: DbCredentials| async move {
+//!         connect(&creds.username, &creds.password).await
+//!     })
+//!     .await?;
+//!
+//! println!("logged in with the {} version", login.version_stage);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+use std::future::Future;
+
+use aws_config::SdkConfig;
+use serde::de::DeserializeOwned;
+
+use crate::secretsmanager::get_secret_value;
+use crate::{Error, Result};
+
+/// The staging labels tried by default, in order.
+pub const DEFAULT_STAGES: [&str; 3] = ["AWSCURRENT", "AWSPENDING", "AWSPREVIOUS"];
+
+/// A boxed error returned by a login attempt.
+pub type LoginError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Tries a login with each version of a secret in turn; see the [module docs](self).
+pub struct CredentialFallback<'a> {
+    config: &'a SdkConfig,
+    secret_id: &'a str,
+    stages: Vec<String>,
+    on_invalidate: Option<Box<dyn FnOnce() + Send + 'a>>,
+}
+
+/// The result of a successful [`CredentialFallback::try_login`].
+#[derive(Debug, Clone)]
+pub struct Login<T> {
+    /// The value returned by the login closure.
+    pub value: T,
+    /// Staging label of the version which worked.
+    pub version_stage: String,
+    /// Id of the version which worked.
+    pub version_id: String,
+}
+
+impl<'a> CredentialFallback<'a> {
+    /// Creates a fallback for the given secret, which tries the
+    /// [`DEFAULT_STAGES`] in order.
+    pub fn new(config: &'a SdkConfig, secret_id: &'a str) -> Self {
+        Self {
+            config,
+            secret_id,
+            stages: DEFAULT_STAGES.iter().map(|s| s.to_string()).collect(),
+            on_invalidate: None,
+        }
+    }
+
+    /// Sets the staging labels to try, in order.
+    pub fn stages<I, S>(mut self, stages: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.stages = stages.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets a callback which is invoked (at most once) when the login fails
+    /// with the first version, so that any cached copy of the secret can be
+    /// invalidated; for example with `Refresher::refresh_now`.
+    pub fn on_invalidate(mut self, invalidate: impl FnOnce() + Send + 'a) -> Self {
+        self.on_invalidate = Some(Box::new(invalidate));
+        self
+    }
+
+    /// Retrieves each version of the secret in turn, de-serializes it, and
+    /// calls `login` with it, until `login` succeeds.
+    ///
+    /// Versions which don't exist (such as `AWSPENDING` outside of a rotation)
+    /// are skipped, as are versions which can't be de-serialized. Any other
+    /// error while retrieving a version is returned immediately. If no version
+    /// works, [`Error::CredentialsRejected`] is returned.
+    pub async fn try_login<C, T, E, F, Fut>(mut self, mut login: F) -> Result<Login<T>>
+    where
+        C: DeserializeOwned,
+        F: FnMut(C) -> Fut,
+        Fut: Future<Output = std::result::Result<T, E>>,
+        E: Into<LoginError>,
+    {
+        let mut last_error = None;
+        let mut tried = Vec::new();
+
+        for stage in &self.stages {
+            let secret =
+                match get_secret_value(self.config, self.secret_id, Some(stage), None).await {
+                    Ok(secret) => secret,
+                    Err(e) if e.is_not_found() => continue,
+                    Err(e) => return Err(e),
+                };
+            tried.push(stage.clone());
+
+            let result: std::result::Result<T, LoginError> =
+                match serde_json::from_str::<C>(&secret.secret_string) {
+                    Ok(credentials) => login(credentials).await.map_err(Into::into),
+                    Err(e) => Err(e.into()),
+                };
+            match result {
+                Ok(value) => {
+                    return Ok(Login {
+                        value,
+                        version_stage: stage.clone(),
+                        version_id: secret.version_id,
+                    })
+                }
+                Err(e) => last_error = Some(e),
+            }
+
+            if let Some(invalidate) = self.on_invalidate.take() {
+                invalidate();
+            }
+        }
+
+        Err(Error::CredentialsRejected {
+            secret_name: self.secret_id.to_owned(),
+            stages: tried,
+            source: last_error,
+        })
+    }
+}
+
+impl fmt::Debug for CredentialFallback<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CredentialFallback")
+            .field("secret_id", &self.secret_id)
+            .field("stages", &self.stages)
+            .finish()
+    }
+}
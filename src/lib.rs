@@ -65,8 +65,16 @@
 //! #### Available features
 //!
 //! * `all` - Enables support for AWS Secrets Manager and SSM Parameter Store.
+//! * `cli` - Builds the `aws-secrets` command-line tool (implies `all`).
+//! * `metrics` - Enables counters and histograms of requests to AWS, using the `metrics` facade.
 //! * `params` - Enables support for AWS SSM Parameter Store.
+//! * `refresh` - Enables keeping secrets and parameters warm in a background `tokio` task,
+//!   and watching secrets for changes.
+//! * `rotation` - Enables the `rotation` module, for writing secret rotation Lambda functions.
 //! * `sm` - Enables support for AWS Secrets Manager.
+//! * `template` - Enables rendering of config templates which reference secrets and parameters.
+//! * `test-util` - Enables the `test_util` module, for testing against canned HTTP responses.
+//! * `tracing` - Enables `tracing` spans around each request to AWS, which never record values.
 //!
 //! #### Enabling Features
 //!
@@ -82,22 +90,69 @@
 //! [`README.md`]: https://github.com/rnag/aws-secrets
 //!
 
+pub mod assume_role;
+#[cfg(feature = "refresh")]
+pub mod changes;
+#[cfg(all(feature = "sm", feature = "params"))]
+pub mod coalesce;
+mod config_builder;
 mod errors;
+#[cfg(any(feature = "sm", feature = "params"))]
+pub mod failover;
+#[cfg(feature = "sm")]
+pub mod fallback;
 #[cfg(feature = "params")]
 mod params;
+#[cfg(feature = "refresh")]
+pub mod refresh;
+#[cfg(feature = "rotation")]
+pub mod rotation;
+#[cfg(all(feature = "sm", feature = "params"))]
+pub mod secret_ref;
 #[cfg(feature = "sm")]
 mod secretsmanager;
+#[cfg(all(feature = "sm", feature = "params"))]
+pub mod store;
+#[cfg(any(feature = "sm", feature = "params"))]
+mod telemetry;
+#[cfg(feature = "template")]
+pub mod template;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+#[cfg(any(feature = "sm", feature = "params"))]
+pub mod timeout;
 mod types;
+#[cfg(any(feature = "sm", feature = "params"))]
+mod util;
 
 pub use aws_config as config;
-pub use aws_config::load_from_env as config_from_env;
+#[cfg(feature = "params")]
+pub use aws_sdk_ssm::model::{ParameterTier, ParameterType};
+pub use config_builder::{ConfigBuilder, ENDPOINT_URL_ENV_VAR};
 pub use errors::Error;
 #[cfg(feature = "params")]
-pub use params::SSMParamExt;
+pub use params::{
+    describe_parameters, LabelChange, ParameterFilter, ParameterMetadata, ParameterValue,
+    ParameterVersion, SSMParamExt,
+};
+#[cfg(all(feature = "sm", feature = "params"))]
+pub use secret_ref::{resolve, SecretRef};
 #[cfg(feature = "sm")]
-pub use secretsmanager::SecretsExt;
+pub use secretsmanager::{
+    list_secrets, PolicyValidation, PolicyValidationError, ReplicaRegion, ReplicationStatus,
+    RotationConfig, RotationResult, RotationRules, SecretDescription, SecretFilter, SecretSummary,
+    SecretValue, SecretVersion, SecretsExt,
+};
 pub use types::Result;
 
+/// Loads the shared `SdkConfig` from the environment, as with
+/// `aws_config::load_from_env`, but also honoring [`ENDPOINT_URL_ENV_VAR`].
+///
+/// Use a [`ConfigBuilder`] to override other settings.
+pub async fn config_from_env() -> config::SdkConfig {
+    ConfigBuilder::new().load().await
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
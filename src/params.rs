@@ -1,7 +1,21 @@
+use std::time::SystemTime;
+
 use async_trait::async_trait;
 use aws_config::SdkConfig;
+use aws_sdk_ssm::model::{
+    Parameter, ParameterHistory, ParameterStringFilter, ParameterTier, ParameterType,
+    ResourceTypeForTagging, Tag,
+};
+use aws_sdk_ssm::output::{
+    AddTagsToResourceOutput, LabelParameterVersionOutput, PutParameterOutput,
+    UnlabelParameterVersionOutput,
+};
 use aws_sdk_ssm::Client;
+use futures_util::stream::{BoxStream, StreamExt, TryStreamExt};
+use futures_util::TryFutureExt;
 
+use crate::telemetry::{Observe, SSM};
+use crate::util::{paginate, to_system_time};
 use crate::{Error, Result};
 
 /// Trait for `str` types, enables interaction with [AWS SSM] Parameter Store.
@@ -15,17 +29,353 @@ pub trait SSMParamExt {
     async fn get_secure_string(self, config: &SdkConfig) -> Result<String>;
     /// Retrieves a parameter (with data type `StringList`) from SSM Parameter Store.
     async fn get_string_list(self, config: &SdkConfig) -> Result<Vec<String>>;
+    /// Retrieves all parameters under this path (recursively) from SSM Parameter Store,
+    /// as `(name, value)` pairs. `SecureString` values are decrypted.
+    async fn get_params_by_path(self, config: &SdkConfig) -> Result<Vec<(String, String)>>;
+    /// Creates or overwrites a parameter (with data type `String`) in SSM Parameter Store.
+    async fn put_string(self, config: &SdkConfig, value: &str) -> Result<PutParameterOutput>;
+    /// Creates or overwrites a parameter (with data type `SecureString`) in SSM Parameter Store.
+    async fn put_secure_string(self, config: &SdkConfig, value: &str)
+        -> Result<PutParameterOutput>;
+    /// Creates or overwrites a parameter (with data type `StringList`) in SSM Parameter Store.
+    async fn put_string_list(
+        self,
+        config: &SdkConfig,
+        values: &[&str],
+    ) -> Result<PutParameterOutput>;
+    /// Set or update the value of a **tag** on a parameter that lives in
+    /// SSM Parameter Store.
+    ///
+    /// Named differently from `SecretsExt::set_tag`, so
+    /// that both traits can be imported together.
+    async fn set_param_tag(
+        self,
+        config: &SdkConfig,
+        key: &str,
+        value: &str,
+    ) -> Result<AddTagsToResourceOutput>;
+    /// Retrieves every version of a parameter from SSM Parameter Store,
+    /// oldest first. `SecureString` values are decrypted.
+    async fn get_parameter_history(self, config: &SdkConfig) -> Result<Vec<ParameterVersion>>;
+    /// Restores an older version of a parameter, by storing its value
+    /// (with the same type and KMS key) as a new version.
+    ///
+    /// The current tier, allowed pattern and data type are kept, as they
+    /// apply to the parameter rather than to a version; in particular, SSM
+    /// can't move a parameter back from the `Advanced` tier to `Standard`.
+    ///
+    /// Returns [`Error::NotFound`] if the version doesn't exist.
+    async fn rollback_parameter(
+        self,
+        config: &SdkConfig,
+        version: i64,
+    ) -> Result<PutParameterOutput>;
+    /// Retrieves (and decrypts) the version of a parameter which has the
+    /// given label, along with its version number.
+    async fn get_labeled(self, config: &SdkConfig, label: &str) -> Result<ParameterValue>;
+    /// Attaches labels to a version of a parameter, moving them from any
+    /// other version which has them. If `version` is `None`, the latest
+    /// version is labeled.
+    async fn label_parameter_version(
+        self,
+        config: &SdkConfig,
+        version: Option<i64>,
+        labels: &[&str],
+    ) -> Result<LabelChange>;
+    /// Removes labels from a version of a parameter.
+    async fn unlabel_parameter_version(
+        self,
+        config: &SdkConfig,
+        version: i64,
+        labels: &[&str],
+    ) -> Result<LabelChange>;
+}
+
+/// A version of a parameter, as returned by `ssm:GetParameter`.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct ParameterValue {
+    /// Name of the parameter.
+    pub name: String,
+    /// Value of the parameter.
+    pub value: String,
+    /// Version number.
+    pub version: i64,
+    /// The version or label used to select this version, e.g. `:prod`.
+    pub selector: Option<String>,
+    /// Type of the parameter, e.g. `SecureString`.
+    pub param_type: Option<String>,
+    /// When this version was created.
+    pub last_modified_date: Option<SystemTime>,
+}
+
+impl From<Parameter> for ParameterValue {
+    fn from(param: Parameter) -> Self {
+        Self {
+            name: param.name.unwrap_or_default(),
+            value: param.value.unwrap_or_default(),
+            version: param.version,
+            selector: param.selector,
+            param_type: param.r#type.map(|t| t.as_str().to_owned()),
+            last_modified_date: to_system_time(param.last_modified_date),
+        }
+    }
+}
+
+/// The result of labeling or unlabeling a version of a parameter.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct LabelChange {
+    /// The version which was labeled or unlabeled.
+    pub version: i64,
+    /// The labels which were attached or removed.
+    pub labels: Vec<String>,
+    /// The labels which were rejected, e.g. because they are not valid
+    /// or (when unlabeling) not attached to the version.
+    pub invalid_labels: Vec<String>,
+}
+
+impl LabelChange {
+    fn labeled(output: LabelParameterVersionOutput, labels: &[&str]) -> Self {
+        let invalid_labels = output.invalid_labels.unwrap_or_default();
+        Self {
+            version: output.parameter_version,
+            labels: labels
+                .iter()
+                .filter(|label| !invalid_labels.iter().any(|l| l == *label))
+                .map(|label| label.to_string())
+                .collect(),
+            invalid_labels,
+        }
+    }
+
+    fn unlabeled(output: UnlabelParameterVersionOutput, version: i64) -> Self {
+        Self {
+            version,
+            labels: output.removed_labels.unwrap_or_default(),
+            invalid_labels: output.invalid_labels.unwrap_or_default(),
+        }
+    }
+}
+
+/// A version of a parameter, as returned by `ssm:GetParameterHistory`.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct ParameterVersion {
+    /// Name of the parameter.
+    pub name: String,
+    /// Version number.
+    pub version: i64,
+    /// Value of this version.
+    pub value: String,
+    /// Type of the parameter, e.g. `SecureString`.
+    pub param_type: Option<String>,
+    /// ID of the KMS key used to encrypt a `SecureString` parameter.
+    pub key_id: Option<String>,
+    /// Labels attached to this version.
+    pub labels: Vec<String>,
+    /// ARN of the user or role which created this version.
+    pub last_modified_user: Option<String>,
+    /// When this version was created.
+    pub last_modified_date: Option<SystemTime>,
+    /// Description of the parameter, as of this version.
+    pub description: Option<String>,
+    /// Regular expression which values of the parameter must match.
+    pub allowed_pattern: Option<String>,
+    /// Tier of this version, e.g. `Standard`.
+    pub tier: Option<String>,
+    /// Data type of the parameter, e.g. `text` or `aws:ec2:image`.
+    pub data_type: Option<String>,
+}
+
+impl From<ParameterHistory> for ParameterVersion {
+    fn from(history: ParameterHistory) -> Self {
+        Self {
+            name: history.name.unwrap_or_default(),
+            version: history.version,
+            value: history.value.unwrap_or_default(),
+            param_type: history.r#type.map(|t| t.as_str().to_owned()),
+            key_id: history.key_id,
+            labels: history.labels.unwrap_or_default(),
+            last_modified_user: history.last_modified_user,
+            last_modified_date: to_system_time(history.last_modified_date),
+            description: history.description,
+            allowed_pattern: history.allowed_pattern,
+            tier: history.tier.map(|t| t.as_str().to_owned()),
+            data_type: history.data_type,
+        }
+    }
+}
+
+/// The details of a parameter (but not its value), as returned by
+/// `ssm:DescribeParameters`.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct ParameterMetadata {
+    /// Name of the parameter.
+    pub name: String,
+    /// Type of the parameter, e.g. `SecureString`.
+    pub param_type: Option<String>,
+    /// ID of the KMS key used to encrypt a `SecureString` parameter.
+    pub key_id: Option<String>,
+    /// When the parameter was last changed.
+    pub last_modified_date: Option<SystemTime>,
+    /// ARN of the user or role which last changed the parameter.
+    pub last_modified_user: Option<String>,
+    /// Description of the parameter.
+    pub description: Option<String>,
+    /// Regular expression which values of the parameter must match.
+    pub allowed_pattern: Option<String>,
+    /// Current version of the parameter.
+    pub version: i64,
+    /// Tier of the parameter, e.g. `Standard`.
+    pub tier: Option<String>,
+    /// Data type of the parameter, e.g. `text` or `aws:ec2:image`.
+    pub data_type: Option<String>,
+}
+
+impl From<aws_sdk_ssm::model::ParameterMetadata> for ParameterMetadata {
+    fn from(metadata: aws_sdk_ssm::model::ParameterMetadata) -> Self {
+        Self {
+            name: metadata.name.unwrap_or_default(),
+            param_type: metadata.r#type.map(|t| t.as_str().to_owned()),
+            key_id: metadata.key_id,
+            last_modified_date: to_system_time(metadata.last_modified_date),
+            last_modified_user: metadata.last_modified_user,
+            description: metadata.description,
+            allowed_pattern: metadata.allowed_pattern,
+            version: metadata.version,
+            tier: metadata.tier.map(|t| t.as_str().to_owned()),
+            data_type: metadata.data_type,
+        }
+    }
+}
+
+/// Filters for [`describe_parameters`]; a parameter must match all filters
+/// to be listed.
+#[derive(Debug, Clone, Default)]
+pub struct ParameterFilter {
+    filters: Vec<ParameterStringFilter>,
+}
+
+impl ParameterFilter {
+    /// Creates an empty filter, which matches all parameters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn with(mut self, key: impl Into<String>, option: Option<&str>, value: Option<&str>) -> Self {
+        self.filters.push(
+            ParameterStringFilter::builder()
+                .key(key)
+                .set_option(option.map(str::to_owned))
+                .set_values(value.map(|v| vec![v.to_owned()]))
+                .build(),
+        );
+        self
+    }
+
+    /// Only lists parameters under `path`: directly under it, or at any
+    /// depth if `recursive` is set.
+    pub fn path(self, path: &str, recursive: bool) -> Self {
+        let option = if recursive { "Recursive" } else { "OneLevel" };
+        self.with("Path", Some(option), Some(path))
+    }
+
+    /// Only lists parameters whose name starts with `prefix`.
+    pub fn name_prefix(self, prefix: &str) -> Self {
+        self.with("Name", Some("BeginsWith"), Some(prefix))
+    }
+
+    /// Only lists parameters of the given type.
+    pub fn param_type(self, param_type: ParameterType) -> Self {
+        self.with("Type", Some("Equals"), Some(param_type.as_str()))
+    }
+
+    /// Only lists parameters in the given tier.
+    pub fn tier(self, tier: ParameterTier) -> Self {
+        self.with("Tier", Some("Equals"), Some(tier.as_str()))
+    }
+
+    /// Only lists `SecureString` parameters encrypted with the given KMS key.
+    pub fn key_id(self, key_id: &str) -> Self {
+        self.with("KeyId", Some("Equals"), Some(key_id))
+    }
+
+    /// Only lists parameters with a version which has the given label.
+    pub fn label(self, label: &str) -> Self {
+        self.with("Label", Some("Equals"), Some(label))
+    }
+
+    /// Only lists parameters which have the given tag. If `value` is set,
+    /// the tag must also have that value.
+    pub fn tag(self, key: &str, value: Option<&str>) -> Self {
+        self.with(format!("tag:{key}"), value.map(|_| "Equals"), value)
+    }
+}
+
+/// Lists the parameters in SSM Parameter Store which match `filter`.
+///
+/// `NextToken` pagination is handled transparently: each page of results is
+/// fetched as the stream is polled, and an error ends the stream.
+///
+/// ```no_run
+/// use aws_secrets::{describe_parameters, ParameterFilter};
+/// use futures_util::TryStreamExt;
+///
+/// # async fn run() -> aws_secrets::Result<()> {
+/// let config = aws_secrets::config_from_env().await;
+/// let filter = ParameterFilter::new().path("/app/", true).tag("team", Some("platform"));
+///
+/// let mut params = describe_parameters(&config, &filter);
+/// while let Some(param) = params.try_next().await? {
+///     println!("{} was last changed by {:?}", param.name, param.last_modified_user);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn describe_parameters(
+    config: &SdkConfig,
+    filter: &ParameterFilter,
+) -> BoxStream<'static, Result<ParameterMetadata>> {
+    let request = Client::new(config)
+        .describe_parameters()
+        .set_parameter_filters(if filter.filters.is_empty() {
+            None
+        } else {
+            Some(filter.filters.clone())
+        });
+
+    let config = config.clone();
+
+    paginate(move |next_token| {
+        let request = request.clone().set_next_token(next_token);
+        let config = config.clone();
+        async move {
+            let resp = request
+                .send()
+                .map_err(|e| Error::DescribeParams(e).or_timeout(None))
+                .observe(SSM, "DescribeParameters", &config, "")
+                .await?;
+            Ok((resp.parameters.unwrap_or_default(), resp.next_token))
+        }
+    })
+    .map_ok(ParameterMetadata::from)
+    .boxed()
 }
 
 /// Retrieves a parameter from AWS SSM Parameter Store;
 /// inspired by the [Create Parameter] example.
 ///
+/// The name may include a version or label selector, as in `name:3`
+/// or `name:prod`.
+///
 /// [Create Parameter]: https://github.com/awslabs/aws-sdk-rust/blob/main/examples/ssm/src/bin/create-parameter.rs
-async fn get_string_with_decryption<'a>(
+pub(crate) async fn get_parameter_with_decryption<'a>(
     config: &'a SdkConfig,
     param_name: &'a str,
     with_decryption: bool,
-) -> Result<String> {
+) -> Result<ParameterValue> {
     let client = Client::new(config);
 
     let resp = client
@@ -33,15 +383,55 @@ async fn get_string_with_decryption<'a>(
         .name(param_name)
         .with_decryption(with_decryption)
         .send()
-        .await
-        .map_err(|e| Error::ReadParam {
-            source: e,
-            param_name: param_name.to_owned(),
-        })?;
+        .map_err(|e| {
+            Error::ReadParam {
+                source: e,
+                param_name: param_name.to_owned(),
+            }
+            .or_timeout(Some(param_name))
+        })
+        .observe(SSM, "GetParameter", config, param_name)
+        .await?;
 
-    let secret_param = resp.parameter.unwrap();
+    Ok(resp.parameter.unwrap().into())
+}
+
+/// Retrieves the value of a parameter; see [`get_parameter_with_decryption`].
+pub(crate) async fn get_string_with_decryption<'a>(
+    config: &'a SdkConfig,
+    param_name: &'a str,
+    with_decryption: bool,
+) -> Result<String> {
+    let param = get_parameter_with_decryption(config, param_name, with_decryption).await?;
+    Ok(param.value)
+}
 
-    Ok(secret_param.value.unwrap())
+/// Creates or overwrites a parameter of the given data type in
+/// AWS SSM Parameter Store.
+pub(crate) async fn put_string_with_type<'a>(
+    config: &'a SdkConfig,
+    param_name: &'a str,
+    value: &'a str,
+    param_type: ParameterType,
+) -> Result<PutParameterOutput> {
+    let client = Client::new(config);
+
+    client
+        .put_parameter()
+        .name(param_name)
+        .value(value)
+        .r#type(param_type)
+        .overwrite(true)
+        .send()
+        .map_err(|e| {
+            Error::PutParam {
+                source: e,
+                param_name: param_name.to_owned(),
+            }
+            .or_timeout(Some(param_name))
+        })
+        .observe(SSM, "PutParameter", config, param_name)
+        .await
 }
 
 #[async_trait]
@@ -58,4 +448,229 @@ impl SSMParamExt for &str {
         let value = get_string_with_decryption(config, self, false).await?;
         Ok(value.split(',').map(str::to_string).collect())
     }
+
+    async fn get_params_by_path(self, config: &SdkConfig) -> Result<Vec<(String, String)>> {
+        let client = Client::new(config);
+
+        let req = client
+            .get_parameters_by_path()
+            .path(self)
+            .recursive(true)
+            .with_decryption(true);
+
+        let mut params = Vec::new();
+        let mut next_token = None;
+
+        loop {
+            let resp = req
+                .clone()
+                .set_next_token(next_token)
+                .send()
+                .map_err(|e| {
+                    Error::ReadParamsByPath {
+                        source: e,
+                        path: self.to_owned(),
+                    }
+                    .or_timeout(Some(self))
+                })
+                .observe(SSM, "GetParametersByPath", config, self)
+                .await?;
+
+            params.extend(
+                resp.parameters
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|p| Some((p.name?, p.value?))),
+            );
+
+            next_token = resp.next_token;
+            if next_token.is_none() {
+                break;
+            }
+        }
+
+        Ok(params)
+    }
+
+    async fn put_string(self, config: &SdkConfig, value: &str) -> Result<PutParameterOutput> {
+        put_string_with_type(config, self, value, ParameterType::String).await
+    }
+
+    async fn put_secure_string(
+        self,
+        config: &SdkConfig,
+        value: &str,
+    ) -> Result<PutParameterOutput> {
+        put_string_with_type(config, self, value, ParameterType::SecureString).await
+    }
+
+    async fn put_string_list(
+        self,
+        config: &SdkConfig,
+        values: &[&str],
+    ) -> Result<PutParameterOutput> {
+        let value = values.join(",");
+        put_string_with_type(config, self, &value, ParameterType::StringList).await
+    }
+
+    async fn set_param_tag(
+        self,
+        config: &SdkConfig,
+        key: &str,
+        value: &str,
+    ) -> Result<AddTagsToResourceOutput> {
+        let client = Client::new(config);
+
+        let tag = Tag::builder().key(key).value(value).build();
+
+        client
+            .add_tags_to_resource()
+            .resource_type(ResourceTypeForTagging::Parameter)
+            .resource_id(self)
+            .tags(tag)
+            .send()
+            .map_err(|e| {
+                Error::SetParamTag {
+                    source: e,
+                    param_name: self.to_owned(),
+                }
+                .or_timeout(Some(self))
+            })
+            .observe(SSM, "AddTagsToResource", config, self)
+            .await
+    }
+
+    async fn get_parameter_history(self, config: &SdkConfig) -> Result<Vec<ParameterVersion>> {
+        let client = Client::new(config);
+
+        let req = client
+            .get_parameter_history()
+            .name(self)
+            .with_decryption(true);
+
+        let mut versions = Vec::new();
+        let mut next_token = None;
+
+        loop {
+            let resp = req
+                .clone()
+                .set_next_token(next_token)
+                .send()
+                .map_err(|e| {
+                    Error::ReadParamHistory {
+                        source: e,
+                        param_name: self.to_owned(),
+                    }
+                    .or_timeout(Some(self))
+                })
+                .observe(SSM, "GetParameterHistory", config, self)
+                .await?;
+
+            versions.extend(
+                resp.parameters
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(Into::into),
+            );
+
+            next_token = resp.next_token;
+            if next_token.is_none() {
+                break;
+            }
+        }
+
+        Ok(versions)
+    }
+
+    async fn rollback_parameter(
+        self,
+        config: &SdkConfig,
+        version: i64,
+    ) -> Result<PutParameterOutput> {
+        let history = self.get_parameter_history(config).await?;
+        let old = history
+            .into_iter()
+            .find(|v| v.version == version)
+            .ok_or_else(|| Error::NotFound {
+                name: format!("{self}:{version}"),
+            })?;
+
+        let client = Client::new(config);
+
+        client
+            .put_parameter()
+            .name(self)
+            .value(old.value)
+            .set_type(old.param_type.as_deref().map(ParameterType::from))
+            .set_key_id(old.key_id)
+            .overwrite(true)
+            .send()
+            .map_err(|e| {
+                Error::PutParam {
+                    source: e,
+                    param_name: self.to_owned(),
+                }
+                .or_timeout(Some(self))
+            })
+            .observe(SSM, "PutParameter", config, self)
+            .await
+    }
+
+    async fn get_labeled(self, config: &SdkConfig, label: &str) -> Result<ParameterValue> {
+        get_parameter_with_decryption(config, &format!("{self}:{label}"), true).await
+    }
+
+    async fn label_parameter_version(
+        self,
+        config: &SdkConfig,
+        version: Option<i64>,
+        labels: &[&str],
+    ) -> Result<LabelChange> {
+        let client = Client::new(config);
+
+        let resp = client
+            .label_parameter_version()
+            .name(self)
+            .set_parameter_version(version)
+            .set_labels(Some(labels.iter().map(|l| l.to_string()).collect()))
+            .send()
+            .map_err(|e| {
+                Error::LabelParam {
+                    source: e,
+                    param_name: self.to_owned(),
+                }
+                .or_timeout(Some(self))
+            })
+            .observe(SSM, "LabelParameterVersion", config, self)
+            .await?;
+
+        Ok(LabelChange::labeled(resp, labels))
+    }
+
+    async fn unlabel_parameter_version(
+        self,
+        config: &SdkConfig,
+        version: i64,
+        labels: &[&str],
+    ) -> Result<LabelChange> {
+        let client = Client::new(config);
+
+        let resp = client
+            .unlabel_parameter_version()
+            .name(self)
+            .parameter_version(version)
+            .set_labels(Some(labels.iter().map(|l| l.to_string()).collect()))
+            .send()
+            .map_err(|e| {
+                Error::UnlabelParam {
+                    source: e,
+                    param_name: self.to_owned(),
+                }
+                .or_timeout(Some(self))
+            })
+            .observe(SSM, "UnlabelParameterVersion", config, self)
+            .await?;
+
+        Ok(LabelChange::unlabeled(resp, version))
+    }
 }
@@ -0,0 +1,371 @@
+//! Keep secrets and parameters warm in the background.
+//!
+//! A [`Refresher`] fetches a set of secrets and parameters once, then
+//! re-fetches them on an interval in a background `tokio` task. The latest
+//! value of each one is available through a cheap [`Watch`] handle, so the
+//! request path never waits on AWS. If a refresh fails, the last good value
+//! keeps being served.
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use aws_secrets::refresh::Refresher;
+//! use aws_secrets::store::AwsStore;
+//!
+//! # async fn run() -> aws_secrets::Result<()> {
+//! let store = AwsStore::new(aws_secrets::config_from_env().await);
+//! let refresher = Refresher::builder(store)
+//!     .interval(Duration::from_secs(300))
+//!     .secret("prod/db")
+//!     .parameter("/app/api_url")
+//!     .start()
+//!     .await?;
+//!
+//! let password = refresher.secret("prod/db").unwrap();
+//! println!("current value has {} characters", password.get().len());
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{watch, Notify};
+use tokio::task::JoinHandle;
+
+use crate::store::SecretStore;
+#[cfg(any(feature = "tracing", feature = "metrics"))]
+use crate::telemetry;
+use crate::{Error, Result};
+
+/// The default interval between two refreshes.
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Identifies a value kept warm by a [`Refresher`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Key {
+    /// A secret in AWS Secrets Manager, by name or ARN.
+    Secret(String),
+    /// A (decrypted) parameter in AWS SSM Parameter Store.
+    Parameter(String),
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Secret(name) => write!(f, "secret {name:?}"),
+            Self::Parameter(name) => write!(f, "parameter {name:?}"),
+        }
+    }
+}
+
+impl Key {
+    /// Returns the kind of value, as a metrics label or tracing field.
+    #[cfg(any(feature = "tracing", feature = "metrics"))]
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Secret(_) => "secret",
+            Self::Parameter(_) => "parameter",
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    fn name(&self) -> &str {
+        match self {
+            Self::Secret(name) | Self::Parameter(name) => name,
+        }
+    }
+}
+
+/// Callback invoked when a background refresh fails.
+type ErrorHook = Box<dyn Fn(&Key, &Error) + Send + Sync>;
+
+/// Builder for a [`Refresher`].
+pub struct RefresherBuilder {
+    store: Arc<dyn SecretStore>,
+    interval: Duration,
+    keys: Vec<Key>,
+    on_error: Option<ErrorHook>,
+}
+
+/// Handle to the latest value of a secret or parameter.
+///
+/// Cloning a `Watch` is cheap, and reading it never blocks on AWS.
+#[derive(Debug, Clone)]
+pub struct Watch {
+    rx: watch::Receiver<Arc<String>>,
+}
+
+/// Keeps a set of secrets and parameters warm, by re-fetching them
+/// in a background task. The task stops when the `Refresher` is dropped.
+pub struct Refresher {
+    watches: HashMap<Key, Watch>,
+    trigger: Arc<Notify>,
+    task: JoinHandle<()>,
+}
+
+impl RefresherBuilder {
+    /// Sets the interval between two refreshes; defaults to [`DEFAULT_INTERVAL`].
+    ///
+    /// If the values are cached elsewhere with a TTL, this should be shorter
+    /// than the TTL, so that they are refreshed before they expire.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Registers a secret to keep warm.
+    pub fn secret(mut self, secret_id: impl Into<String>) -> Self {
+        self.keys.push(Key::Secret(secret_id.into()));
+        self
+    }
+
+    /// Registers a parameter to keep warm. The value is decrypted.
+    pub fn parameter(mut self, name: impl Into<String>) -> Self {
+        self.keys.push(Key::Parameter(name.into()));
+        self
+    }
+
+    /// Sets a callback invoked whenever a background refresh fails,
+    /// for example to log the error.
+    pub fn on_error(mut self, hook: impl Fn(&Key, &Error) + Send + Sync + 'static) -> Self {
+        self.on_error = Some(Box::new(hook));
+        self
+    }
+
+    /// Fetches all registered values, then starts refreshing them in the background.
+    ///
+    /// Returns an error if any of the initial fetches fails. Must be called
+    /// from within a `tokio` runtime.
+    pub async fn start(self) -> Result<Refresher> {
+        let mut watches = HashMap::new();
+        let mut senders = Vec::new();
+
+        for key in self.keys {
+            if watches.contains_key(&key) {
+                continue;
+            }
+            let value = fetch(self.store.as_ref(), &key).await?;
+            let (tx, rx) = watch::channel(Arc::new(value));
+            watches.insert(key.clone(), Watch { rx });
+            senders.push((key, tx));
+        }
+
+        let trigger = Arc::new(Notify::new());
+        let task = tokio::spawn(refresh_loop(
+            self.store,
+            self.interval,
+            senders,
+            trigger.clone(),
+            self.on_error,
+        ));
+
+        Ok(Refresher {
+            watches,
+            trigger,
+            task,
+        })
+    }
+}
+
+impl Refresher {
+    /// Creates a builder which fetches values from the given store.
+    pub fn builder(store: impl SecretStore + 'static) -> RefresherBuilder {
+        Self::builder_from_arc(Arc::new(store))
+    }
+
+    /// Creates a builder which fetches values from a shared store.
+    pub fn builder_from_arc(store: Arc<dyn SecretStore>) -> RefresherBuilder {
+        RefresherBuilder {
+            store,
+            interval: DEFAULT_INTERVAL,
+            keys: Vec::new(),
+            on_error: None,
+        }
+    }
+
+    /// Returns a handle to the latest value of a registered secret.
+    pub fn secret(&self, secret_id: &str) -> Option<Watch> {
+        self.watch(&Key::Secret(secret_id.to_owned()))
+    }
+
+    /// Returns a handle to the latest value of a registered parameter.
+    pub fn parameter(&self, name: &str) -> Option<Watch> {
+        self.watch(&Key::Parameter(name.to_owned()))
+    }
+
+    fn watch(&self, key: &Key) -> Option<Watch> {
+        let watch = self.watches.get(key).cloned();
+        #[cfg(feature = "tracing")]
+        telemetry::trace_cache_lookup(key.kind(), key.name(), watch.is_some());
+        #[cfg(feature = "metrics")]
+        telemetry::record_cache_lookup(key.kind(), watch.is_some());
+        watch
+    }
+
+    /// Re-fetches all values now, instead of waiting for the next interval.
+    ///
+    /// This returns immediately; use [`Watch::changed`] to wait for a new value.
+    pub fn refresh_now(&self) {
+        self.trigger.notify_one();
+    }
+}
+
+impl Drop for Refresher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl fmt::Debug for Refresher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Refresher")
+            .field("keys", &self.watches.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl Watch {
+    /// Returns the latest value.
+    pub fn get(&self) -> Arc<String> {
+        self.rx.borrow().clone()
+    }
+
+    /// Waits until the value changes.
+    ///
+    /// Returns an error if the `Refresher` has been dropped.
+    pub async fn changed(&mut self) -> std::result::Result<(), watch::error::RecvError> {
+        self.rx.changed().await
+    }
+}
+
+async fn fetch(store: &dyn SecretStore, key: &Key) -> Result<String> {
+    match key {
+        Key::Secret(secret_id) => store.get_secret_string(secret_id).await,
+        Key::Parameter(name) => store.get_parameter(name, true).await,
+    }
+}
+
+async fn refresh_loop(
+    store: Arc<dyn SecretStore>,
+    interval: Duration,
+    senders: Vec<(Key, watch::Sender<Arc<String>>)>,
+    trigger: Arc<Notify>,
+    on_error: Option<ErrorHook>,
+) {
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            _ = trigger.notified() => {}
+        }
+
+        for (key, tx) in &senders {
+            match fetch(store.as_ref(), key).await {
+                Ok(value) => {
+                    // Only wake up watchers if the value actually changed.
+                    tx.send_if_modified(|current| {
+                        if **current == value {
+                            return false;
+                        }
+                        *current = Arc::new(value);
+                        true
+                    });
+                }
+                Err(e) => {
+                    if let Some(hook) = &on_error {
+                        hook(key, &e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MemoryStore;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    /// A store which fails all reads while `failing` is set.
+    struct FlakyStore {
+        inner: MemoryStore,
+        failing: AtomicBool,
+    }
+
+    #[async_trait]
+    impl SecretStore for FlakyStore {
+        async fn get_secret_string(&self, secret_id: &str) -> Result<String> {
+            if self.failing.load(Ordering::SeqCst) {
+                return Err(Error::Unknown);
+            }
+            self.inner.get_secret_string(secret_id).await
+        }
+
+        async fn put_secret_string(&self, secret_id: &str, value: &str) -> Result<()> {
+            self.inner.put_secret_string(secret_id, value).await
+        }
+
+        async fn get_parameter(&self, name: &str, with_decryption: bool) -> Result<String> {
+            self.inner.get_parameter(name, with_decryption).await
+        }
+
+        async fn put_parameter(&self, name: &str, value: &str, secure: bool) -> Result<()> {
+            self.inner.put_parameter(name, value, secure).await
+        }
+    }
+
+    #[tokio::test]
+    async fn refreshes_values() {
+        let store = Arc::new(MemoryStore::new().with_parameter("/app/flag", "off"));
+        let refresher = Refresher::builder_from_arc(store.clone())
+            .interval(Duration::from_millis(10))
+            .parameter("/app/flag")
+            .start()
+            .await
+            .unwrap();
+
+        let mut flag = refresher.parameter("/app/flag").unwrap();
+        assert_eq!(*flag.get(), "off");
+
+        store.put_parameter("/app/flag", "on", false).await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), flag.changed())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(*flag.get(), "on");
+    }
+
+    #[tokio::test]
+    async fn keeps_last_good_value_on_failure() {
+        let store = Arc::new(FlakyStore {
+            inner: MemoryStore::new().with_secret("prod/db", "v1"),
+            failing: AtomicBool::new(false),
+        });
+        let errors = Arc::new(AtomicUsize::new(0));
+        let counter = errors.clone();
+
+        let refresher = Refresher::builder_from_arc(store.clone())
+            .interval(Duration::from_millis(10))
+            .secret("prod/db")
+            .on_error(move |_, _| {
+                counter.fetch_add(1, Ordering::SeqCst);
+            })
+            .start()
+            .await
+            .unwrap();
+
+        store.failing.store(true, Ordering::SeqCst);
+        store.put_secret_string("prod/db", "v2").await.unwrap();
+        let failed = tokio::time::timeout(Duration::from_secs(5), async {
+            while errors.load(Ordering::SeqCst) == 0 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await;
+        assert!(failed.is_ok(), "timed out waiting for a failed refresh");
+        assert_eq!(*refresher.secret("prod/db").unwrap().get(), "v1");
+    }
+}
@@ -0,0 +1,305 @@
+//! A framework for custom secret rotation Lambda functions.
+//!
+//! Secrets Manager rotates a secret by invoking a Lambda function four times,
+//! once per [`RotationStep`]. A [`Rotator`] implements the state machine
+//! shared by all rotation functions: it validates the event, keeps track of
+//! the `AWSPENDING` and `AWSCURRENT` staging labels, and makes each step
+//! idempotent. The parts specific to a kind of secret (generating a new
+//! value, and applying it to e.g. a database) are provided by a
+//! [`RotationHandler`].
+//!
+//! ```no_run
+//! use async_trait::async_trait;
+//! use aws_secrets::rotation::{BoxError, RotationContext, RotationEvent, RotationHandler, Rotator};
+//!
+//! struct ApiKeyRotation;
+//!
+//! #[async_trait]
+//! impl RotationHandler for ApiKeyRotation {
+//!     async fn create_secret(&self, _: &RotationContext, _current: &str) -> Result<String, BoxError> {
+//!         Ok(format!("{{\"api_key\":\"{}\"}}", "new-random-key"))
+//!     }
+//!
+//!     async fn set_secret(&self, _: &RotationContext, _pending: &str, _current: &str) -> Result<(), BoxError> {
+//!         // register the new key with the upstream service
+//!         Ok(())
+//!     }
+//!
+//!     async fn test_secret(&self, _: &RotationContext, _pending: &str) -> Result<(), BoxError> {
+//!         // call the upstream service with the new key
+//!         Ok(())
+//!     }
+//! }
+//!
+//! // In the Lambda function handler:
+//! # async fn handler(event: serde_json::Value) -> aws_secrets::Result<()> {
+//! let config = aws_secrets::config_from_env().await;
+//! let event: RotationEvent = serde_json::from_value(event)?;
+//! Rotator::new(config, ApiKeyRotation).handle(&event).await
+//! # }
+//! ```
+
+use std::fmt;
+
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use serde::Deserialize;
+
+use crate::secretsmanager::{
+    get_secret_value, put_secret_value, update_version_stage, SecretDescription,
+};
+use crate::{Error, Result, SecretsExt};
+
+/// Staging label of the version which is in use.
+pub const AWSCURRENT: &str = "AWSCURRENT";
+/// Staging label of the version which is being rotated in.
+pub const AWSPENDING: &str = "AWSPENDING";
+
+/// A boxed error returned by a [`RotationHandler`].
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// A step of the rotation, as sent by Secrets Manager.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RotationStep {
+    /// Generate a new version of the secret, staged as `AWSPENDING`.
+    CreateSecret,
+    /// Apply the `AWSPENDING` version, e.g. change the password in the database.
+    SetSecret,
+    /// Verify that the `AWSPENDING` version works.
+    TestSecret,
+    /// Move the `AWSCURRENT` label to the `AWSPENDING` version.
+    FinishSecret,
+}
+
+/// The event a rotation Lambda function is invoked with.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct RotationEvent {
+    /// The rotation step to perform.
+    pub step: RotationStep,
+    /// ARN of the secret being rotated.
+    pub secret_id: String,
+    /// Id of the new version of the secret.
+    pub client_request_token: String,
+}
+
+/// Details of the rotation, passed to each [`RotationHandler`] method.
+#[derive(Debug, Clone)]
+pub struct RotationContext {
+    /// The shared AWS config, e.g. to read other secrets or parameters.
+    pub config: SdkConfig,
+    /// ARN of the secret being rotated.
+    pub secret_id: String,
+    /// Id of the new (`AWSPENDING`) version of the secret.
+    pub token: String,
+    /// The rotation step being performed.
+    pub step: RotationStep,
+}
+
+/// The parts of a rotation which are specific to a kind of secret.
+///
+/// Each method receives secret values as raw `SecretString`s.
+#[async_trait]
+pub trait RotationHandler: Send + Sync {
+    /// Generates a new value for the secret, given the current one.
+    async fn create_secret(
+        &self,
+        ctx: &RotationContext,
+        current: &str,
+    ) -> std::result::Result<String, BoxError>;
+
+    /// Applies the pending value, e.g. changes the password in the database.
+    /// The current value can be used to log in.
+    async fn set_secret(
+        &self,
+        ctx: &RotationContext,
+        pending: &str,
+        current: &str,
+    ) -> std::result::Result<(), BoxError>;
+
+    /// Verifies that the pending value works, e.g. by logging in with it.
+    async fn test_secret(
+        &self,
+        ctx: &RotationContext,
+        pending: &str,
+    ) -> std::result::Result<(), BoxError>;
+
+    /// Called after the pending version has been made `AWSCURRENT`.
+    /// Does nothing by default.
+    async fn finish_secret(&self, _ctx: &RotationContext) -> std::result::Result<(), BoxError> {
+        Ok(())
+    }
+}
+
+/// Runs the rotation state machine for a [`RotationHandler`].
+pub struct Rotator<H> {
+    config: SdkConfig,
+    handler: H,
+}
+
+impl<H: RotationHandler> Rotator<H> {
+    /// Creates a rotator which uses the given shared AWS config.
+    pub fn new(config: SdkConfig, handler: H) -> Self {
+        Self { config, handler }
+    }
+
+    /// Performs one step of the rotation.
+    ///
+    /// Each step can safely be retried: a step whose work has already been
+    /// done (for example, the `AWSPENDING` version already exists in
+    /// `createSecret`) does nothing.
+    pub async fn handle(&self, event: &RotationEvent) -> Result<()> {
+        let ctx = RotationContext {
+            config: self.config.clone(),
+            secret_id: event.secret_id.clone(),
+            token: event.client_request_token.clone(),
+            step: event.step,
+        };
+        let description = event
+            .secret_id
+            .as_str()
+            .describe_secret(&self.config)
+            .await?;
+
+        if !description.rotation_enabled {
+            return Err(rotation_error(
+                &ctx,
+                "rotation is not enabled for the secret",
+            ));
+        }
+        let stages = match description.version_ids_to_stages.get(&ctx.token) {
+            Some(stages) => stages,
+            None => return Err(rotation_error(&ctx, "version is not staged for rotation")),
+        };
+        if stages.iter().any(|s| s == AWSCURRENT) {
+            // The rotation has already completed.
+            return Ok(());
+        }
+        if !stages.iter().any(|s| s == AWSPENDING) {
+            return Err(rotation_error(&ctx, "version is not staged as AWSPENDING"));
+        }
+
+        match event.step {
+            RotationStep::CreateSecret => self.create_secret(&ctx).await,
+            RotationStep::SetSecret => {
+                let pending = self.pending(&ctx).await?;
+                let current = self.current(&ctx).await?;
+                self.handler
+                    .set_secret(&ctx, &pending, &current)
+                    .await
+                    .map_err(|e| handler_error(&ctx, e))
+            }
+            RotationStep::TestSecret => {
+                let pending = self.pending(&ctx).await?;
+                self.handler
+                    .test_secret(&ctx, &pending)
+                    .await
+                    .map_err(|e| handler_error(&ctx, e))
+            }
+            RotationStep::FinishSecret => self.finish_secret(&ctx, &description).await,
+        }
+    }
+
+    async fn create_secret(&self, ctx: &RotationContext) -> Result<()> {
+        let current = self.current(ctx).await?;
+
+        match get_secret_value(
+            &self.config,
+            &ctx.secret_id,
+            Some(AWSPENDING),
+            Some(&ctx.token),
+        )
+        .await
+        {
+            // Already created by a previous invocation.
+            Ok(_) => return Ok(()),
+            Err(e) if e.is_not_found() => {}
+            Err(e) => return Err(e),
+        }
+
+        let pending = self
+            .handler
+            .create_secret(ctx, &current)
+            .await
+            .map_err(|e| handler_error(ctx, e))?;
+
+        put_secret_value(
+            &self.config,
+            &ctx.secret_id,
+            &pending,
+            Some(&ctx.token),
+            &[AWSPENDING],
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn finish_secret(
+        &self,
+        ctx: &RotationContext,
+        description: &SecretDescription,
+    ) -> Result<()> {
+        let current_version = description.current_version_id();
+
+        update_version_stage(
+            &self.config,
+            &ctx.secret_id,
+            AWSCURRENT,
+            Some(&ctx.token),
+            current_version,
+        )
+        .await?;
+
+        self.handler
+            .finish_secret(ctx)
+            .await
+            .map_err(|e| handler_error(ctx, e))
+    }
+
+    async fn current(&self, ctx: &RotationContext) -> Result<String> {
+        let value = get_secret_value(&self.config, &ctx.secret_id, Some(AWSCURRENT), None).await?;
+        Ok(value.secret_string)
+    }
+
+    async fn pending(&self, ctx: &RotationContext) -> Result<String> {
+        let value = get_secret_value(
+            &self.config,
+            &ctx.secret_id,
+            Some(AWSPENDING),
+            Some(&ctx.token),
+        )
+        .await?;
+        Ok(value.secret_string)
+    }
+}
+
+impl<H> fmt::Debug for Rotator<H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Rotator").finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for RotationStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::CreateSecret => "createSecret",
+            Self::SetSecret => "setSecret",
+            Self::TestSecret => "testSecret",
+            Self::FinishSecret => "finishSecret",
+        })
+    }
+}
+
+fn handler_error(ctx: &RotationContext, source: BoxError) -> Error {
+    Error::Rotation {
+        secret_name: ctx.secret_id.clone(),
+        step: ctx.step.to_string(),
+        source,
+    }
+}
+
+fn rotation_error(ctx: &RotationContext, message: &str) -> Error {
+    handler_error(ctx, message.into())
+}
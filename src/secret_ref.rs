@@ -0,0 +1,515 @@
+//! References to secrets and parameters, written as strings.
+//!
+//! A [`SecretRef`] can be parsed from any of the following forms:
+//!
+//! * `secretsmanager://<secret-id>?version-stage=<stage>&version-id=<id>#<field>`
+//!   (or `sm://...`)
+//! * `ssm://<parameter-name>?decrypt=<bool>&version=<version>&label=<label>#<field>`;
+//!   for names starting with `/`, write e.g. `ssm:///path/name`
+//! * the CloudFormation [dynamic reference] syntax, such as
+//!   `{{resolve:secretsmanager:<secret-id>:SecretString:<json-key>:<stage>:<id>}}`,
+//!   `{{resolve:ssm:<name>:<version>}}` and `{{resolve:ssm-secure:<name>:<version>}}`
+//! * the short forms `sm:<secret-id>#<field>` and `ssm:<parameter-name>`
+//!
+//! All query parameters are optional. `<field>` is a `.`-separated path
+//! into a JSON value, for example `#db.password`.
+//! In the URI forms, a `?`, `#`, `&` or `%` in a name or value is written
+//! as a `%XX` escape, e.g. `sm://what%3F`, which is how they are displayed.
+//!
+//! [dynamic reference]: https://docs.aws.amazon.com/AWSCloudFormation/latest/UserGuide/dynamic-references.html
+
+use std::fmt;
+use std::str::FromStr;
+
+use aws_config::SdkConfig;
+use serde_json::Value;
+
+use crate::params::get_string_with_decryption;
+use crate::secretsmanager::get_secret_string_version;
+use crate::{Error, Result};
+
+/// Where the value of a [`SecretRef`] lives.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Source {
+    /// A secret in AWS Secrets Manager.
+    SecretsManager {
+        /// Name or ARN of the secret.
+        secret_id: String,
+        /// Staging label of the version to retrieve, e.g. `AWSPREVIOUS`.
+        version_stage: Option<String>,
+        /// Unique identifier of the version to retrieve.
+        version_id: Option<String>,
+    },
+    /// A parameter in AWS SSM Parameter Store.
+    ParameterStore {
+        /// Name of the parameter.
+        name: String,
+        /// Version number or label of the parameter to retrieve.
+        selector: Option<String>,
+        /// Whether to decrypt a `SecureString` parameter.
+        decrypt: bool,
+    },
+}
+
+/// A reference to a secret or parameter, optionally selecting a field
+/// of its (JSON) value. See the [module docs](self) for the supported syntax.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SecretRef {
+    /// Where the value lives.
+    pub source: Source,
+    /// `.`-separated path of the field to select from a JSON value.
+    pub field: Option<String>,
+}
+
+impl Source {
+    /// Retrieves the raw value from the backend service.
+    pub async fn fetch(&self, config: &SdkConfig) -> Result<String> {
+        match self {
+            Self::SecretsManager {
+                secret_id,
+                version_stage,
+                version_id,
+            } => {
+                get_secret_string_version(
+                    config,
+                    secret_id,
+                    version_stage.as_deref(),
+                    version_id.as_deref(),
+                )
+                .await
+            }
+            Self::ParameterStore {
+                name,
+                selector,
+                decrypt,
+            } => match selector {
+                Some(selector) => {
+                    get_string_with_decryption(config, &format!("{name}:{selector}"), *decrypt)
+                        .await
+                }
+                None => get_string_with_decryption(config, name, *decrypt).await,
+            },
+        }
+    }
+
+    /// Name of the secret or parameter.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::SecretsManager { secret_id, .. } => secret_id,
+            Self::ParameterStore { name, .. } => name,
+        }
+    }
+}
+
+impl SecretRef {
+    /// Retrieves the referenced value, and selects the field (if any).
+    pub async fn resolve(&self, config: &SdkConfig) -> Result<String> {
+        let value = self.source.fetch(config).await?;
+        self.select(value)
+    }
+
+    /// Selects the referenced field (if any) from an already retrieved value.
+    pub fn select(&self, value: String) -> Result<String> {
+        let path = match &self.field {
+            Some(path) => path,
+            None => return Ok(value),
+        };
+
+        let json: Value = serde_json::from_str(&value)?;
+        let selected = path
+            .split('.')
+            .try_fold(&json, |value, key| value.get(key))
+            .ok_or_else(|| Error::MissingField {
+                secret_name: self.source.name().to_owned(),
+                field: path.clone(),
+            })?;
+
+        Ok(match selected {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        })
+    }
+}
+
+/// Parses a reference string and retrieves the value it points to.
+///
+/// This is a shorthand for `reference.parse::<SecretRef>()?.resolve(config)`.
+pub async fn resolve(reference: &str, config: &SdkConfig) -> Result<String> {
+    reference.parse::<SecretRef>()?.resolve(config).await
+}
+
+impl FromStr for SecretRef {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let invalid = |message: &str| Error::InvalidRef {
+            reference: s.to_owned(),
+            message: message.to_owned(),
+        };
+
+        if let Some(inner) = s
+            .strip_prefix("{{")
+            .and_then(|s| s.strip_suffix("}}"))
+            .and_then(|s| s.trim().strip_prefix("resolve:"))
+        {
+            return parse_dynamic_reference(inner).map_err(invalid);
+        }
+
+        let (scheme, rest) = s.split_once(':').ok_or_else(|| invalid("missing scheme"))?;
+        let (rest, is_uri) = match rest.strip_prefix("//") {
+            Some(rest) => (rest, true),
+            None => (rest, false),
+        };
+
+        let (rest, field) = match rest.split_once('#') {
+            Some((rest, field)) => (rest, Some(percent_decode(field))),
+            None => (rest, None),
+        };
+        let (name, query) = match rest.split_once('?') {
+            Some((name, query)) if is_uri => (name, query),
+            _ => (rest, ""),
+        };
+        let name = percent_decode(name.trim());
+        if name.is_empty() {
+            return Err(invalid("empty name"));
+        }
+        if field.as_deref() == Some("") {
+            return Err(invalid("empty field"));
+        }
+
+        let mut params = Vec::new();
+        for pair in query.split('&').filter(|p| !p.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            params.push((key, percent_decode(value)));
+        }
+
+        let source = match scheme {
+            "secretsmanager" | "sm" => {
+                let mut version_stage = None;
+                let mut version_id = None;
+                for (key, value) in params {
+                    match key {
+                        "version-stage" => version_stage = Some(value),
+                        "version-id" => version_id = Some(value),
+                        _ => return Err(invalid(&format!("unknown query parameter {key:?}"))),
+                    }
+                }
+                Source::SecretsManager {
+                    secret_id: name,
+                    version_stage,
+                    version_id,
+                }
+            }
+            "ssm" => {
+                let mut selector = None;
+                let mut decrypt = true;
+                for (key, value) in params {
+                    match key {
+                        "version" | "label" => selector = Some(value),
+                        "decrypt" => {
+                            decrypt = value
+                                .parse()
+                                .map_err(|_| invalid("`decrypt` must be `true` or `false`"))?
+                        }
+                        _ => return Err(invalid(&format!("unknown query parameter {key:?}"))),
+                    }
+                }
+                Source::ParameterStore {
+                    name,
+                    selector,
+                    decrypt,
+                }
+            }
+            _ => return Err(invalid("unknown scheme")),
+        };
+
+        Ok(Self { source, field })
+    }
+}
+
+/// Parses the inner part of a CloudFormation dynamic reference, i.e.
+/// `{{resolve:<inner>}}`.
+fn parse_dynamic_reference(inner: &str) -> std::result::Result<SecretRef, &'static str> {
+    let (service, rest) = inner.split_once(':').ok_or("missing service")?;
+    let non_empty = |s: Option<&str>| s.filter(|s| !s.is_empty()).map(str::to_owned);
+
+    match service {
+        "secretsmanager" => {
+            // A secret ARN contains colons itself, so it spans 7 segments.
+            let segments: Vec<&str> = rest.split(':').collect();
+            let id_len = if rest.starts_with("arn:") { 7 } else { 1 };
+            if segments.len() < id_len {
+                return Err("invalid secret ARN");
+            }
+            let secret_id = segments[..id_len].join(":");
+            let mut parts = segments[id_len..].iter().copied();
+
+            match parts.next() {
+                None | Some("SecretString") => {}
+                Some(_) => return Err("only `SecretString` is supported"),
+            }
+            let field = non_empty(parts.next());
+            let version_stage = non_empty(parts.next());
+            let version_id = non_empty(parts.next());
+            if parts.next().is_some() {
+                return Err("too many segments");
+            }
+            if secret_id.is_empty() {
+                return Err("empty name");
+            }
+
+            Ok(SecretRef {
+                source: Source::SecretsManager {
+                    secret_id,
+                    version_stage,
+                    version_id,
+                },
+                field,
+            })
+        }
+        "ssm" | "ssm-secure" => {
+            let (name, selector) = match rest.rsplit_once(':') {
+                Some((name, version)) if version.parse::<u64>().is_ok() => {
+                    (name, Some(version.to_owned()))
+                }
+                _ => (rest, None),
+            };
+            if name.is_empty() {
+                return Err("empty name");
+            }
+
+            Ok(SecretRef {
+                source: Source::ParameterStore {
+                    name: name.to_owned(),
+                    selector,
+                    decrypt: service == "ssm-secure",
+                },
+                field: None,
+            })
+        }
+        _ => Err("unknown service"),
+    }
+}
+
+/// Decodes `%XX` escapes in a URI component. Invalid escapes are kept as-is.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = |b: u8| (b as char).to_digit(16);
+            if let (Some(hi), Some(lo)) = (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                out.push((hi * 16 + lo) as u8);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Escapes the characters which would otherwise end a URI component when
+/// parsed back: `%`, `?`, `#` and `&`.
+fn percent_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '%' | '?' | '#' | '&' => out.push_str(&format!("%{:02X}", c as u8)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+impl fmt::Display for SecretRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut query = Vec::new();
+        match &self.source {
+            Source::SecretsManager {
+                secret_id,
+                version_stage,
+                version_id,
+            } => {
+                write!(f, "secretsmanager://{}", percent_encode(secret_id))?;
+                if let Some(stage) = version_stage {
+                    query.push(format!("version-stage={}", percent_encode(stage)));
+                }
+                if let Some(id) = version_id {
+                    query.push(format!("version-id={}", percent_encode(id)));
+                }
+            }
+            Source::ParameterStore {
+                name,
+                selector,
+                decrypt,
+            } => {
+                write!(f, "ssm://{}", percent_encode(name))?;
+                if let Some(selector) = selector {
+                    match selector.parse::<u64>() {
+                        Ok(_) => query.push(format!("version={selector}")),
+                        Err(_) => query.push(format!("label={}", percent_encode(selector))),
+                    }
+                }
+                if !decrypt {
+                    query.push("decrypt=false".to_owned());
+                }
+            }
+        }
+        if !query.is_empty() {
+            write!(f, "?{}", query.join("&"))?;
+        }
+        if let Some(field) = &self.field {
+            write!(f, "#{}", percent_encode(field))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secret(
+        id: &str,
+        stage: Option<&str>,
+        version: Option<&str>,
+        field: Option<&str>,
+    ) -> SecretRef {
+        SecretRef {
+            source: Source::SecretsManager {
+                secret_id: id.to_owned(),
+                version_stage: stage.map(str::to_owned),
+                version_id: version.map(str::to_owned),
+            },
+            field: field.map(str::to_owned),
+        }
+    }
+
+    fn param(name: &str, selector: Option<&str>, decrypt: bool) -> SecretRef {
+        SecretRef {
+            source: Source::ParameterStore {
+                name: name.to_owned(),
+                selector: selector.map(str::to_owned),
+                decrypt,
+            },
+            field: None,
+        }
+    }
+
+    #[test]
+    fn parses_uris() {
+        assert_eq!(
+            "secretsmanager://prod/db?version-stage=AWSPREVIOUS#json.field"
+                .parse::<SecretRef>()
+                .unwrap(),
+            secret("prod/db", Some("AWSPREVIOUS"), None, Some("json.field"))
+        );
+        assert_eq!(
+            "ssm:///path/name?decrypt=true"
+                .parse::<SecretRef>()
+                .unwrap(),
+            param("/path/name", None, true)
+        );
+        assert_eq!(
+            "ssm://name?decrypt=false&label=prod"
+                .parse::<SecretRef>()
+                .unwrap(),
+            param("name", Some("prod"), false)
+        );
+        assert_eq!(
+            "sm:prod/db#password".parse::<SecretRef>().unwrap(),
+            secret("prod/db", None, None, Some("password"))
+        );
+    }
+
+    #[test]
+    fn parses_dynamic_references() {
+        assert_eq!(
+            "{{resolve:secretsmanager:MySecret:SecretString:password:AWSPREVIOUS}}"
+                .parse::<SecretRef>()
+                .unwrap(),
+            secret("MySecret", Some("AWSPREVIOUS"), None, Some("password"))
+        );
+        assert_eq!(
+            "{{resolve:secretsmanager:arn:aws:secretsmanager:us-east-1:123456789012:secret:MySecret-a1b2c3:SecretString:::abc}}"
+                .parse::<SecretRef>()
+                .unwrap(),
+            secret(
+                "arn:aws:secretsmanager:us-east-1:123456789012:secret:MySecret-a1b2c3",
+                None,
+                Some("abc"),
+                None
+            )
+        );
+        assert_eq!(
+            "{{resolve:ssm-secure:/app/key:3}}"
+                .parse::<SecretRef>()
+                .unwrap(),
+            param("/app/key", Some("3"), true)
+        );
+        assert_eq!(
+            "{{resolve:ssm:/app/url}}".parse::<SecretRef>().unwrap(),
+            param("/app/url", None, false)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_references() {
+        for s in [
+            "prod/db",
+            "vault://x",
+            "sm:",
+            "secretsmanager://x?stage=1",
+            "ssm://x?decrypt=maybe",
+            "{{resolve:secretsmanager:x:SecretBinary}}",
+        ] {
+            assert!(
+                matches!(s.parse::<SecretRef>(), Err(Error::InvalidRef { .. })),
+                "{s}"
+            );
+        }
+    }
+
+    #[test]
+    fn round_trips_through_display() {
+        for s in [
+            "secretsmanager://prod/db?version-stage=AWSPREVIOUS#json.field",
+            "ssm:///path/name?version=3&decrypt=false",
+        ] {
+            assert_eq!(s.parse::<SecretRef>().unwrap().to_string(), s);
+        }
+    }
+
+    #[test]
+    fn round_trips_escaped_names() {
+        for r in [
+            secret("what?#100%", Some("a&b"), None, Some("x#y")),
+            param("/path/a?b", Some("c#d"), true),
+        ] {
+            assert_eq!(r.to_string().parse::<SecretRef>().unwrap(), r);
+        }
+        assert_eq!(
+            secret("a?b", None, None, Some("c#d")).to_string(),
+            "secretsmanager://a%3Fb#c%23d"
+        );
+    }
+
+    #[test]
+    fn selects_nested_field() {
+        let r = secret("db", None, None, Some("primary.port"));
+        let value = r#"{"primary":{"port":5432}}"#.to_owned();
+        assert_eq!(r.select(value).unwrap(), "5432");
+
+        let r = secret("db", None, None, Some("replica.port"));
+        assert!(matches!(
+            r.select(r#"{"primary":{}}"#.to_owned()),
+            Err(Error::MissingField { .. })
+        ));
+    }
+}
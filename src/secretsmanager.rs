@@ -1,10 +1,25 @@
+use std::collections::HashMap;
+use std::time::SystemTime;
+
 use async_trait::async_trait;
 use aws_config::SdkConfig;
-use aws_sdk_secretsmanager::model::Tag;
-use aws_sdk_secretsmanager::output::TagResourceOutput;
+use aws_sdk_secretsmanager::model::{
+    Filter, FilterNameStringType, ReplicaRegionType, ReplicationStatusType, RotationRulesType,
+    SecretListEntry, SecretVersionsListEntry, Tag,
+};
+use aws_sdk_secretsmanager::output::{
+    CancelRotateSecretOutput, DescribeSecretOutput, GetSecretValueOutput, PutSecretValueOutput,
+    RotateSecretOutput, TagResourceOutput, UpdateSecretVersionStageOutput,
+};
 use aws_sdk_secretsmanager::Client;
+use futures_util::stream::{BoxStream, StreamExt, TryStreamExt};
+use futures_util::TryFutureExt;
 use serde::de::DeserializeOwned;
+use serde_json::Value;
 
+use crate::failover::RegionFailover;
+use crate::telemetry::{Observe, SECRETS_MANAGER};
+use crate::util::{paginate, to_system_time};
 use crate::{Error, Result};
 
 /// Trait for `str` types, enables interaction with [AWS Secrets Manager].
@@ -17,30 +32,830 @@ pub trait SecretsExt {
     ///
     /// [Get Secret Value]: https://github.com/awslabs/aws-sdk-rust/blob/main/examples/secretsmanager/src/bin/get-secret-value.rs
     async fn get_secret<T: DeserializeOwned>(self, config: &SdkConfig) -> Result<T>;
+    /// Retrieves the raw `SecretString` of a secret from AWS Secrets Manager,
+    /// without de-serializing it.
+    async fn get_secret_string(self, config: &SdkConfig) -> Result<String>;
+    /// Retrieves the raw `SecretString` of a replicated secret, trying the
+    /// region of `config` first, then each of the `replicas` (configs for
+    /// the replica regions) in turn, as a [`RegionFailover`] does.
+    ///
+    /// Only transient errors (see [`Error::is_transient`]) fall back to the
+    /// next region; other errors, such as a missing secret, are returned
+    /// immediately. If every region fails, the error from the last one is
+    /// returned.
+    async fn get_secret_string_with_replicas(
+        self,
+        config: &SdkConfig,
+        replicas: &[SdkConfig],
+    ) -> Result<String>;
+    /// Stores a new `SecretString` value for a secret that lives in
+    /// AWS Secrets Manager.
+    ///
+    /// The new version is automatically staged as `AWSCURRENT`.
+    async fn put_secret_string(
+        self,
+        config: &SdkConfig,
+        value: &str,
+    ) -> Result<PutSecretValueOutput>;
+    /// Lists all secrets in AWS Secrets Manager whose name starts with
+    /// this prefix. An empty prefix lists all secrets.
+    ///
+    /// Pages of results are fetched as the stream is polled; see
+    /// [`list_secrets`] for other filters.
+    fn list_secrets(self, config: &SdkConfig) -> BoxStream<'static, Result<SecretSummary>>;
+    /// Retrieves the details of a secret (but not its value) from
+    /// AWS Secrets Manager.
+    async fn describe_secret(self, config: &SdkConfig) -> Result<SecretDescription>;
     /// Set or update the value of a **tag** on a secret that lives in
     /// AWS Secrets Manager.
     async fn set_tag(self, config: &SdkConfig, key: &str, value: &str)
         -> Result<TagResourceOutput>;
+    /// Starts a rotation of the secret now, using its existing rotation
+    /// function and rules.
+    async fn rotate_secret(self, config: &SdkConfig) -> Result<RotationResult>;
+    /// Cancels an in-progress rotation of the secret, and turns off
+    /// automatic rotation.
+    async fn cancel_rotate_secret(self, config: &SdkConfig) -> Result<RotationResult>;
+    /// Retrieves the rotation rules of the secret, if rotation has been configured.
+    async fn get_rotation_rules(self, config: &SdkConfig) -> Result<Option<RotationRules>>;
+    /// Turns on (or updates) automatic rotation of the secret.
+    async fn set_rotation(
+        self,
+        config: &SdkConfig,
+        rotation: &RotationConfig,
+    ) -> Result<RotationResult>;
+    /// Lists the versions of the secret which have a staging label. If
+    /// `include_deprecated` is set, versions without a label (which Secrets
+    /// Manager will eventually delete) are also listed.
+    async fn list_secret_version_ids(
+        self,
+        config: &SdkConfig,
+        include_deprecated: bool,
+    ) -> Result<Vec<SecretVersion>>;
+    /// Moves a staging label to the version `move_to_version_id`, and/or
+    /// removes it from the version `remove_from_version_id`.
+    ///
+    /// When moving `AWSCURRENT`, the version which had it must be given as
+    /// `remove_from_version_id`; it is then labeled `AWSPREVIOUS`.
+    async fn update_secret_version_stage(
+        self,
+        config: &SdkConfig,
+        version_stage: &str,
+        move_to_version_id: Option<&str>,
+        remove_from_version_id: Option<&str>,
+    ) -> Result<()>;
+    /// Makes a version of the secret `AWSCURRENT`, e.g. to roll back to
+    /// the `AWSPREVIOUS` version after bad credentials were stored.
+    async fn promote_secret_version(self, config: &SdkConfig, version_id: &str) -> Result<()>;
+    /// Retrieves the resource-based policy attached to the secret, if any.
+    async fn get_resource_policy(self, config: &SdkConfig) -> Result<Option<Value>>;
+    /// Attaches a resource-based policy to the secret, replacing any
+    /// existing one.
+    ///
+    /// Unless `block_public_policy` is `Some(false)`, the policy is rejected
+    /// if it would grant public access to the secret.
+    async fn put_resource_policy(
+        self,
+        config: &SdkConfig,
+        policy: &Value,
+        block_public_policy: Option<bool>,
+    ) -> Result<()>;
+    /// Removes the resource-based policy attached to the secret.
+    async fn delete_resource_policy(self, config: &SdkConfig) -> Result<()>;
+    /// Validates a resource-based policy (for this secret, unless the name
+    /// is empty) without attaching it.
+    async fn validate_resource_policy(
+        self,
+        config: &SdkConfig,
+        policy: &Value,
+    ) -> Result<PolicyValidation>;
+    /// Replicates the secret to other regions, and returns the status of
+    /// all its replicas.
+    ///
+    /// If `force_overwrite` is set, a secret with the same name which
+    /// already exists in one of the regions is overwritten.
+    async fn replicate_secret_to_regions(
+        self,
+        config: &SdkConfig,
+        regions: &[ReplicaRegion],
+        force_overwrite: bool,
+    ) -> Result<Vec<ReplicationStatus>>;
+    /// Deletes the replicas of the secret in the given regions, and returns
+    /// the status of the remaining replicas.
+    async fn remove_regions_from_replication(
+        self,
+        config: &SdkConfig,
+        regions: &[&str],
+    ) -> Result<Vec<ReplicationStatus>>;
+    /// Promotes a replica to a standalone secret. `config` must be for the
+    /// region of the replica.
+    async fn stop_replication_to_replica(self, config: &SdkConfig) -> Result<()>;
+}
+
+/// A version of a secret, and its value.
+#[derive(Debug, Clone)]
+pub struct SecretValue {
+    /// Name of the secret.
+    pub name: String,
+    /// Unique identifier of this version.
+    pub version_id: String,
+    /// Staging labels attached to this version, e.g. `AWSCURRENT`.
+    pub version_stages: Vec<String>,
+    /// The `SecretString` of this version.
+    pub secret_string: String,
+    /// When this version was created.
+    pub created_date: Option<SystemTime>,
+}
+
+impl TryFrom<GetSecretValueOutput> for SecretValue {
+    type Error = Error;
+
+    /// Fails with [`Error::MissingSecretString`] if the secret has no
+    /// `SecretString`, e.g. because it is binary.
+    fn try_from(output: GetSecretValueOutput) -> Result<Self> {
+        let name = output.name.unwrap_or_default();
+        let secret_string = match output.secret_string {
+            Some(value) => value,
+            None => return Err(Error::MissingSecretString { secret_name: name }),
+        };
+
+        Ok(Self {
+            name,
+            version_id: output.version_id.unwrap_or_default(),
+            version_stages: output.version_stages.unwrap_or_default(),
+            secret_string,
+            created_date: to_system_time(output.created_date),
+        })
+    }
+}
+
+/// The result of [`SecretsExt::validate_resource_policy`].
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct PolicyValidation {
+    /// Whether the policy passed all checks.
+    pub passed: bool,
+    /// The problems found in the policy, if any.
+    pub errors: Vec<PolicyValidationError>,
+}
+
+/// A problem found in a resource-based policy.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct PolicyValidationError {
+    /// Name of the check which failed, e.g. `PUBLIC_ACCESS_CHECK`.
+    pub check_name: Option<String>,
+    /// Description of the problem.
+    pub message: Option<String>,
+}
+
+/// A version of a secret (but not its value), as returned by
+/// `secretsmanager:ListSecretVersionIds`.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct SecretVersion {
+    /// Unique identifier of this version.
+    pub version_id: String,
+    /// Staging labels attached to this version, e.g. `AWSCURRENT`.
+    /// Empty for deprecated versions.
+    pub version_stages: Vec<String>,
+    /// When this version was created.
+    pub created_date: Option<SystemTime>,
+    /// When this version was last accessed (rounded to the day).
+    pub last_accessed_date: Option<SystemTime>,
+}
+
+impl SecretVersion {
+    /// Returns true if this version has the given staging label.
+    pub fn has_stage(&self, stage: &str) -> bool {
+        self.version_stages.iter().any(|s| s == stage)
+    }
+}
+
+impl From<SecretVersionsListEntry> for SecretVersion {
+    fn from(entry: SecretVersionsListEntry) -> Self {
+        Self {
+            version_id: entry.version_id.unwrap_or_default(),
+            version_stages: entry.version_stages.unwrap_or_default(),
+            created_date: to_system_time(entry.created_date),
+            last_accessed_date: to_system_time(entry.last_accessed_date),
+        }
+    }
+}
+
+/// The details of a secret, as returned by `secretsmanager:ListSecrets`.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct SecretSummary {
+    /// ARN of the secret.
+    pub arn: String,
+    /// Name of the secret.
+    pub name: String,
+    /// Description of the secret.
+    pub description: Option<String>,
+    /// ARN or alias of the KMS key used to encrypt the secret.
+    pub kms_key_id: Option<String>,
+    /// Whether automatic rotation is turned on.
+    pub rotation_enabled: bool,
+    /// When the secret was last rotated.
+    pub last_rotated_date: Option<SystemTime>,
+    /// When the secret was last changed.
+    pub last_changed_date: Option<SystemTime>,
+    /// When the secret was last accessed (rounded to the day).
+    pub last_accessed_date: Option<SystemTime>,
+    /// When the secret is scheduled to be deleted, if at all.
+    pub deleted_date: Option<SystemTime>,
+    /// When the secret was created.
+    pub created_date: Option<SystemTime>,
+    /// ID of the service which created (and manages) the secret.
+    pub owning_service: Option<String>,
+    /// Region of the primary secret.
+    pub primary_region: Option<String>,
+    /// Tags attached to the secret.
+    pub tags: HashMap<String, String>,
+}
+
+impl From<SecretListEntry> for SecretSummary {
+    fn from(entry: SecretListEntry) -> Self {
+        Self {
+            arn: entry.arn.unwrap_or_default(),
+            name: entry.name.unwrap_or_default(),
+            description: entry.description,
+            kms_key_id: entry.kms_key_id,
+            rotation_enabled: entry.rotation_enabled.unwrap_or_default(),
+            last_rotated_date: to_system_time(entry.last_rotated_date),
+            last_changed_date: to_system_time(entry.last_changed_date),
+            last_accessed_date: to_system_time(entry.last_accessed_date),
+            deleted_date: to_system_time(entry.deleted_date),
+            created_date: to_system_time(entry.created_date),
+            owning_service: entry.owning_service,
+            primary_region: entry.primary_region,
+            tags: to_tag_map(entry.tags),
+        }
+    }
+}
+
+/// Filters for [`list_secrets`].
+///
+/// Each filter matches the *start* of the corresponding field (prefixing the
+/// value with `!` negates it); a secret must match all filters to be listed.
+#[derive(Debug, Clone, Default)]
+pub struct SecretFilter {
+    filters: Vec<Filter>,
+    include_planned_deletion: bool,
+}
+
+impl SecretFilter {
+    /// Creates an empty filter, which matches all secrets.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn with(mut self, key: FilterNameStringType, value: impl Into<String>) -> Self {
+        self.filters
+            .push(Filter::builder().key(key).values(value).build());
+        self
+    }
+
+    /// Only lists secrets whose name starts with `prefix`.
+    pub fn name_prefix(self, prefix: impl Into<String>) -> Self {
+        self.with(FilterNameStringType::Name, prefix)
+    }
+
+    /// Only lists secrets with a tag whose key starts with `key`.
+    pub fn tag_key(self, key: impl Into<String>) -> Self {
+        self.with(FilterNameStringType::TagKey, key)
+    }
+
+    /// Only lists secrets with a tag whose value starts with `value`.
+    pub fn tag_value(self, value: impl Into<String>) -> Self {
+        self.with(FilterNameStringType::TagValue, value)
+    }
+
+    /// Only lists secrets whose description starts with `description`.
+    pub fn description(self, description: impl Into<String>) -> Self {
+        self.with(FilterNameStringType::Description, description)
+    }
+
+    /// Only lists secrets managed by the given service, e.g. `rds`.
+    pub fn owning_service(self, service: impl Into<String>) -> Self {
+        self.with(FilterNameStringType::OwningService, service)
+    }
+
+    /// Also lists secrets which are scheduled for deletion.
+    pub fn include_planned_deletion(mut self, include: bool) -> Self {
+        self.include_planned_deletion = include;
+        self
+    }
+}
+
+/// Lists the secrets in AWS Secrets Manager which match `filter`.
+///
+/// `NextToken` pagination is handled transparently: each page of results is
+/// fetched as the stream is polled, and an error ends the stream.
+///
+/// ```no_run
+/// use aws_secrets::{list_secrets, SecretFilter};
+/// use futures_util::TryStreamExt;
+///
+/// # async fn run() -> aws_secrets::Result<()> {
+/// let config = aws_secrets::config_from_env().await;
+/// let filter = SecretFilter::new().tag_key("team").owning_service("!rds");
+///
+/// let mut secrets = list_secrets(&config, &filter);
+/// while let Some(secret) = secrets.try_next().await? {
+///     println!("{} (rotation enabled: {})", secret.name, secret.rotation_enabled);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn list_secrets(
+    config: &SdkConfig,
+    filter: &SecretFilter,
+) -> BoxStream<'static, Result<SecretSummary>> {
+    let request = Client::new(config)
+        .list_secrets()
+        .set_filters(if filter.filters.is_empty() {
+            None
+        } else {
+            Some(filter.filters.clone())
+        })
+        .include_planned_deletion(filter.include_planned_deletion);
+
+    let config = config.clone();
+
+    paginate(move |next_token| {
+        let request = request.clone().set_next_token(next_token);
+        let config = config.clone();
+        async move {
+            let resp = request
+                .send()
+                .map_err(|e| Error::ListSecrets(e).or_timeout(None))
+                .observe(SECRETS_MANAGER, "ListSecrets", &config, "")
+                .await?;
+            Ok((resp.secret_list.unwrap_or_default(), resp.next_token))
+        }
+    })
+    .map_ok(SecretSummary::from)
+    .boxed()
+}
+
+/// The details of a secret, as returned by `secretsmanager:DescribeSecret`.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct SecretDescription {
+    /// ARN of the secret.
+    pub arn: String,
+    /// Name of the secret.
+    pub name: String,
+    /// Description of the secret.
+    pub description: Option<String>,
+    /// ARN or alias of the KMS key used to encrypt the secret.
+    pub kms_key_id: Option<String>,
+    /// Whether automatic rotation is turned on.
+    pub rotation_enabled: bool,
+    /// ARN of the Lambda function which rotates the secret.
+    pub rotation_lambda_arn: Option<String>,
+    /// When the secret was last rotated.
+    pub last_rotated_date: Option<SystemTime>,
+    /// When the secret was last changed.
+    pub last_changed_date: Option<SystemTime>,
+    /// When the secret was last accessed (rounded to the day).
+    pub last_accessed_date: Option<SystemTime>,
+    /// When the secret is scheduled to be deleted, if at all.
+    pub deleted_date: Option<SystemTime>,
+    /// When the secret was created.
+    pub created_date: Option<SystemTime>,
+    /// ID of the service which created (and manages) the secret.
+    pub owning_service: Option<String>,
+    /// Region of the primary secret.
+    pub primary_region: Option<String>,
+    /// Tags attached to the secret.
+    pub tags: HashMap<String, String>,
+    /// Staging labels attached to each version of the secret, by version id.
+    pub version_ids_to_stages: HashMap<String, Vec<String>>,
+    /// The rotation schedule, if rotation has been configured.
+    pub rotation_rules: Option<RotationRules>,
+    /// The status of each replica of the secret, if it is replicated.
+    pub replication_status: Vec<ReplicationStatus>,
+}
+
+/// The status of a replica of a secret in another region.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct ReplicationStatus {
+    /// Region of the replica.
+    pub region: String,
+    /// ARN or alias of the KMS key used to encrypt the replica.
+    pub kms_key_id: Option<String>,
+    /// Status of the replication, e.g. `InSync` or `Failed`.
+    pub status: Option<String>,
+    /// Details about the status, e.g. why the replication failed.
+    pub status_message: Option<String>,
+    /// When the replica was last accessed (rounded to the day).
+    pub last_accessed_date: Option<SystemTime>,
+}
+
+impl ReplicationStatus {
+    /// Returns true if the replica is up to date with the primary secret.
+    pub fn is_in_sync(&self) -> bool {
+        self.status.as_deref() == Some("InSync")
+    }
+}
+
+impl From<ReplicationStatusType> for ReplicationStatus {
+    fn from(status: ReplicationStatusType) -> Self {
+        Self {
+            region: status.region.unwrap_or_default(),
+            kms_key_id: status.kms_key_id,
+            status: status.status.map(|s| s.as_str().to_owned()),
+            status_message: status.status_message,
+            last_accessed_date: to_system_time(status.last_accessed_date),
+        }
+    }
+}
+
+/// A region to replicate a secret to, for
+/// [`SecretsExt::replicate_secret_to_regions`].
+#[derive(Debug, Clone)]
+pub struct ReplicaRegion {
+    /// The region, e.g. `eu-west-1`.
+    pub region: String,
+    /// ARN or alias of the KMS key to encrypt the replica with. If not set,
+    /// the `aws/secretsmanager` key of that region is used.
+    pub kms_key_id: Option<String>,
+}
+
+impl ReplicaRegion {
+    /// Creates a replica region which uses the default KMS key.
+    pub fn new(region: impl Into<String>) -> Self {
+        Self {
+            region: region.into(),
+            kms_key_id: None,
+        }
+    }
+
+    /// Sets the KMS key to encrypt the replica with.
+    pub fn kms_key_id(mut self, kms_key_id: impl Into<String>) -> Self {
+        self.kms_key_id = Some(kms_key_id.into());
+        self
+    }
+}
+
+impl From<&ReplicaRegion> for ReplicaRegionType {
+    fn from(replica: &ReplicaRegion) -> Self {
+        ReplicaRegionType::builder()
+            .region(&replica.region)
+            .set_kms_key_id(replica.kms_key_id.clone())
+            .build()
+    }
+}
+
+/// When a secret is rotated automatically.
+///
+/// Exactly one of `automatically_after_days` and `schedule_expression`
+/// should be set.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RotationRules {
+    /// Number of days between rotations.
+    pub automatically_after_days: Option<i64>,
+    /// A `rate()` or `cron()` expression, e.g. `cron(0 16 1,15 * ? *)`.
+    pub schedule_expression: Option<String>,
+    /// Length of the rotation window, e.g. `3h`.
+    pub duration: Option<String>,
+}
+
+impl RotationRules {
+    /// Rotates the secret every `days` days.
+    pub fn every_days(days: i64) -> Self {
+        Self {
+            automatically_after_days: Some(days),
+            ..Self::default()
+        }
+    }
+
+    /// Rotates the secret on a `rate()` or `cron()` schedule.
+    pub fn schedule(expression: impl Into<String>) -> Self {
+        Self {
+            schedule_expression: Some(expression.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Sets the length of the rotation window, e.g. `3h`.
+    pub fn duration(mut self, duration: impl Into<String>) -> Self {
+        self.duration = Some(duration.into());
+        self
+    }
+}
+
+impl From<RotationRulesType> for RotationRules {
+    fn from(rules: RotationRulesType) -> Self {
+        Self {
+            automatically_after_days: rules.automatically_after_days,
+            schedule_expression: rules.schedule_expression,
+            duration: rules.duration,
+        }
+    }
+}
+
+impl From<&RotationRules> for RotationRulesType {
+    fn from(rules: &RotationRules) -> Self {
+        RotationRulesType::builder()
+            .set_automatically_after_days(rules.automatically_after_days)
+            .set_schedule_expression(rules.schedule_expression.clone())
+            .set_duration(rules.duration.clone())
+            .build()
+    }
+}
+
+/// The rotation settings passed to [`SecretsExt::set_rotation`].
+#[derive(Debug, Clone)]
+pub struct RotationConfig {
+    /// ARN of the Lambda function which rotates the secret. If not set, the
+    /// secret's current rotation function is kept.
+    pub lambda_arn: Option<String>,
+    /// When to rotate the secret.
+    pub rules: RotationRules,
+    /// Whether to also rotate the secret now; `true` by default.
+    pub rotate_immediately: bool,
+}
+
+impl RotationConfig {
+    /// Creates a config with the given rules, which rotates the secret now
+    /// with its current rotation function.
+    pub fn new(rules: RotationRules) -> Self {
+        Self {
+            lambda_arn: None,
+            rules,
+            rotate_immediately: true,
+        }
+    }
+
+    /// Sets the ARN of the Lambda function which rotates the secret.
+    pub fn lambda_arn(mut self, arn: impl Into<String>) -> Self {
+        self.lambda_arn = Some(arn.into());
+        self
+    }
+
+    /// Sets whether to also rotate the secret now.
+    pub fn rotate_immediately(mut self, rotate_immediately: bool) -> Self {
+        self.rotate_immediately = rotate_immediately;
+        self
+    }
+}
+
+/// The result of starting or cancelling a rotation.
+#[derive(Debug, Clone)]
+pub struct RotationResult {
+    /// ARN of the secret.
+    pub arn: String,
+    /// Name of the secret.
+    pub name: String,
+    /// Id of the new version created by the rotation (or, when cancelling,
+    /// of the version which was being rotated in), if any.
+    pub version_id: Option<String>,
+}
+
+impl From<RotateSecretOutput> for RotationResult {
+    fn from(output: RotateSecretOutput) -> Self {
+        Self {
+            arn: output.arn.unwrap_or_default(),
+            name: output.name.unwrap_or_default(),
+            version_id: output.version_id,
+        }
+    }
+}
+
+impl From<CancelRotateSecretOutput> for RotationResult {
+    fn from(output: CancelRotateSecretOutput) -> Self {
+        Self {
+            arn: output.arn.unwrap_or_default(),
+            name: output.name.unwrap_or_default(),
+            version_id: output.version_id,
+        }
+    }
+}
+
+impl SecretDescription {
+    /// Returns the id of the version which has the given staging label.
+    pub fn version_with_stage(&self, stage: &str) -> Option<&str> {
+        self.version_ids_to_stages
+            .iter()
+            .find(|(_, stages)| stages.iter().any(|s| s == stage))
+            .map(|(id, _)| id.as_str())
+    }
+
+    /// Returns the id of the `AWSCURRENT` version.
+    pub fn current_version_id(&self) -> Option<&str> {
+        self.version_with_stage("AWSCURRENT")
+    }
+}
+
+impl From<DescribeSecretOutput> for SecretDescription {
+    fn from(output: DescribeSecretOutput) -> Self {
+        Self {
+            arn: output.arn.unwrap_or_default(),
+            name: output.name.unwrap_or_default(),
+            description: output.description,
+            kms_key_id: output.kms_key_id,
+            rotation_enabled: output.rotation_enabled.unwrap_or_default(),
+            rotation_lambda_arn: output.rotation_lambda_arn,
+            last_rotated_date: to_system_time(output.last_rotated_date),
+            last_changed_date: to_system_time(output.last_changed_date),
+            last_accessed_date: to_system_time(output.last_accessed_date),
+            deleted_date: to_system_time(output.deleted_date),
+            created_date: to_system_time(output.created_date),
+            owning_service: output.owning_service,
+            primary_region: output.primary_region,
+            tags: to_tag_map(output.tags),
+            version_ids_to_stages: output.version_ids_to_stages.unwrap_or_default(),
+            rotation_rules: output.rotation_rules.map(Into::into),
+            replication_status: output
+                .replication_status
+                .unwrap_or_default()
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        }
+    }
+}
+
+/// Converts SDK tags to a map of tag keys to values.
+fn to_tag_map(tags: Option<Vec<Tag>>) -> HashMap<String, String> {
+    tags.unwrap_or_default()
+        .into_iter()
+        .filter_map(|tag| Some((tag.key?, tag.value.unwrap_or_default())))
+        .collect()
+}
+
+/// Retrieves a specific version of a secret, selected by staging label
+/// and/or version id. If neither is set, the `AWSCURRENT` version is returned.
+pub(crate) async fn get_secret_value(
+    config: &SdkConfig,
+    secret_name: &str,
+    version_stage: Option<&str>,
+    version_id: Option<&str>,
+) -> Result<SecretValue> {
+    let client = Client::new(config);
+
+    let resp = client
+        .get_secret_value()
+        .secret_id(secret_name)
+        .set_version_stage(version_stage.map(str::to_owned))
+        .set_version_id(version_id.map(str::to_owned))
+        .send()
+        .map_err(|e| {
+            Error::ReadSecret {
+                secret_name: secret_name.to_owned(),
+                source: e,
+            }
+            .or_timeout(Some(secret_name))
+        })
+        .observe(SECRETS_MANAGER, "GetSecretValue", config, secret_name)
+        .await?;
+
+    resp.try_into()
+}
+
+/// Retrieves the `SecretString` of a specific version of a secret;
+/// see [`get_secret_value`].
+pub(crate) async fn get_secret_string_version(
+    config: &SdkConfig,
+    secret_name: &str,
+    version_stage: Option<&str>,
+    version_id: Option<&str>,
+) -> Result<String> {
+    let value = get_secret_value(config, secret_name, version_stage, version_id).await?;
+    Ok(value.secret_string)
+}
+
+/// Stores a new version of a secret, with the given client request token
+/// (which becomes the version id) and staging labels.
+pub(crate) async fn put_secret_value(
+    config: &SdkConfig,
+    secret_name: &str,
+    value: &str,
+    client_request_token: Option<&str>,
+    version_stages: &[&str],
+) -> Result<PutSecretValueOutput> {
+    let client = Client::new(config);
+
+    client
+        .put_secret_value()
+        .secret_id(secret_name)
+        .secret_string(value)
+        .set_client_request_token(client_request_token.map(str::to_owned))
+        .set_version_stages(if version_stages.is_empty() {
+            None
+        } else {
+            Some(version_stages.iter().map(|s| s.to_string()).collect())
+        })
+        .send()
+        .map_err(|e| {
+            Error::PutSecret {
+                secret_name: secret_name.to_owned(),
+                source: e,
+            }
+            .or_timeout(Some(secret_name))
+        })
+        .observe(SECRETS_MANAGER, "PutSecretValue", config, secret_name)
+        .await
+}
+
+/// Moves a staging label to a version of a secret, and/or removes it
+/// from another version.
+pub(crate) async fn update_version_stage(
+    config: &SdkConfig,
+    secret_name: &str,
+    version_stage: &str,
+    move_to_version_id: Option<&str>,
+    remove_from_version_id: Option<&str>,
+) -> Result<UpdateSecretVersionStageOutput> {
+    let client = Client::new(config);
+
+    client
+        .update_secret_version_stage()
+        .secret_id(secret_name)
+        .version_stage(version_stage)
+        .set_move_to_version_id(move_to_version_id.map(str::to_owned))
+        .set_remove_from_version_id(remove_from_version_id.map(str::to_owned))
+        .send()
+        .map_err(|e| {
+            Error::UpdateVersionStage {
+                secret_name: secret_name.to_owned(),
+                source: e,
+            }
+            .or_timeout(Some(secret_name))
+        })
+        .observe(
+            SECRETS_MANAGER,
+            "UpdateSecretVersionStage",
+            config,
+            secret_name,
+        )
+        .await
 }
 
 #[async_trait]
 impl SecretsExt for &str {
     async fn get_secret<T: DeserializeOwned>(self, config: &SdkConfig) -> Result<T> {
+        let secret_str = self.get_secret_string(config).await?;
+
+        serde_json::from_str(&secret_str).map_err(Error::DeserializeError)
+    }
+
+    async fn get_secret_string(self, config: &SdkConfig) -> Result<String> {
+        get_secret_string_version(config, self, None, None).await
+    }
+
+    async fn get_secret_string_with_replicas(
+        self,
+        config: &SdkConfig,
+        replicas: &[SdkConfig],
+    ) -> Result<String> {
+        let configs = std::iter::once(config).chain(replicas).cloned();
+        let served = RegionFailover::from_configs(configs)
+            .get_secret_string(self)
+            .await?;
+        Ok(served.value)
+    }
+
+    async fn put_secret_string(
+        self,
+        config: &SdkConfig,
+        value: &str,
+    ) -> Result<PutSecretValueOutput> {
+        put_secret_value(config, self, value, None, &[]).await
+    }
+
+    fn list_secrets(self, config: &SdkConfig) -> BoxStream<'static, Result<SecretSummary>> {
+        let mut filter = SecretFilter::new();
+        if !self.is_empty() {
+            filter = filter.name_prefix(self);
+        }
+        list_secrets(config, &filter)
+    }
+
+    async fn describe_secret(self, config: &SdkConfig) -> Result<SecretDescription> {
         let client = Client::new(config);
 
         let resp = client
-            .get_secret_value()
+            .describe_secret()
             .secret_id(self)
             .send()
-            .await
-            .map_err(|e| Error::ReadSecret {
-                secret_name: self.to_owned(),
-                source: e,
-            })?;
-
-        let secret_str = resp.secret_string().unwrap();
+            .map_err(|e| {
+                Error::DescribeSecret {
+                    secret_name: self.to_owned(),
+                    source: e,
+                }
+                .or_timeout(Some(self))
+            })
+            .observe(SECRETS_MANAGER, "DescribeSecret", config, self)
+            .await?;
 
-        serde_json::from_str(secret_str).map_err(Error::DeserializeError)
+        Ok(resp.into())
     }
 
     async fn set_tag(
@@ -58,7 +873,352 @@ impl SecretsExt for &str {
             .secret_id(self)
             .tags(tag)
             .send()
+            .map_err(|e| Error::SetTag(e).or_timeout(Some(self)))
+            .observe(SECRETS_MANAGER, "TagResource", config, self)
+            .await
+    }
+
+    async fn rotate_secret(self, config: &SdkConfig) -> Result<RotationResult> {
+        let client = Client::new(config);
+
+        let resp = client
+            .rotate_secret()
+            .secret_id(self)
+            .send()
+            .map_err(|e| {
+                Error::RotateSecret {
+                    secret_name: self.to_owned(),
+                    source: e,
+                }
+                .or_timeout(Some(self))
+            })
+            .observe(SECRETS_MANAGER, "RotateSecret", config, self)
+            .await?;
+
+        Ok(resp.into())
+    }
+
+    async fn cancel_rotate_secret(self, config: &SdkConfig) -> Result<RotationResult> {
+        let client = Client::new(config);
+
+        let resp = client
+            .cancel_rotate_secret()
+            .secret_id(self)
+            .send()
+            .map_err(|e| {
+                Error::CancelRotateSecret {
+                    secret_name: self.to_owned(),
+                    source: e,
+                }
+                .or_timeout(Some(self))
+            })
+            .observe(SECRETS_MANAGER, "CancelRotateSecret", config, self)
+            .await?;
+
+        Ok(resp.into())
+    }
+
+    async fn get_rotation_rules(self, config: &SdkConfig) -> Result<Option<RotationRules>> {
+        Ok(self.describe_secret(config).await?.rotation_rules)
+    }
+
+    async fn set_rotation(
+        self,
+        config: &SdkConfig,
+        rotation: &RotationConfig,
+    ) -> Result<RotationResult> {
+        let client = Client::new(config);
+
+        let resp = client
+            .rotate_secret()
+            .secret_id(self)
+            .set_rotation_lambda_arn(rotation.lambda_arn.clone())
+            .rotation_rules((&rotation.rules).into())
+            .rotate_immediately(rotation.rotate_immediately)
+            .send()
+            .map_err(|e| {
+                Error::RotateSecret {
+                    secret_name: self.to_owned(),
+                    source: e,
+                }
+                .or_timeout(Some(self))
+            })
+            .observe(SECRETS_MANAGER, "RotateSecret", config, self)
+            .await?;
+
+        Ok(resp.into())
+    }
+
+    async fn list_secret_version_ids(
+        self,
+        config: &SdkConfig,
+        include_deprecated: bool,
+    ) -> Result<Vec<SecretVersion>> {
+        let client = Client::new(config);
+
+        let req = client
+            .list_secret_version_ids()
+            .secret_id(self)
+            .include_deprecated(include_deprecated);
+
+        let mut versions = Vec::new();
+        let mut next_token = None;
+
+        loop {
+            let resp = req
+                .clone()
+                .set_next_token(next_token)
+                .send()
+                .map_err(|e| {
+                    Error::ListSecretVersions {
+                        secret_name: self.to_owned(),
+                        source: e,
+                    }
+                    .or_timeout(Some(self))
+                })
+                .observe(SECRETS_MANAGER, "ListSecretVersionIds", config, self)
+                .await?;
+
+            versions.extend(
+                resp.versions
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(Into::into),
+            );
+
+            next_token = resp.next_token;
+            if next_token.is_none() {
+                break;
+            }
+        }
+
+        Ok(versions)
+    }
+
+    async fn update_secret_version_stage(
+        self,
+        config: &SdkConfig,
+        version_stage: &str,
+        move_to_version_id: Option<&str>,
+        remove_from_version_id: Option<&str>,
+    ) -> Result<()> {
+        update_version_stage(
+            config,
+            self,
+            version_stage,
+            move_to_version_id,
+            remove_from_version_id,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn promote_secret_version(self, config: &SdkConfig, version_id: &str) -> Result<()> {
+        let description = self.describe_secret(config).await?;
+        let current = description.current_version_id();
+        if current == Some(version_id) {
+            return Ok(());
+        }
+
+        self.update_secret_version_stage(config, "AWSCURRENT", Some(version_id), current)
             .await
-            .map_err(Error::SetTag)
+    }
+
+    async fn get_resource_policy(self, config: &SdkConfig) -> Result<Option<Value>> {
+        let client = Client::new(config);
+
+        let resp = client
+            .get_resource_policy()
+            .secret_id(self)
+            .send()
+            .map_err(|e| {
+                Error::GetResourcePolicy {
+                    secret_name: self.to_owned(),
+                    source: e,
+                }
+                .or_timeout(Some(self))
+            })
+            .observe(SECRETS_MANAGER, "GetResourcePolicy", config, self)
+            .await?;
+
+        match resp.resource_policy {
+            Some(policy) => Ok(Some(serde_json::from_str(&policy)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn put_resource_policy(
+        self,
+        config: &SdkConfig,
+        policy: &Value,
+        block_public_policy: Option<bool>,
+    ) -> Result<()> {
+        let client = Client::new(config);
+
+        client
+            .put_resource_policy()
+            .secret_id(self)
+            .resource_policy(policy.to_string())
+            .set_block_public_policy(block_public_policy)
+            .send()
+            .map_err(|e| {
+                Error::PutResourcePolicy {
+                    secret_name: self.to_owned(),
+                    source: e,
+                }
+                .or_timeout(Some(self))
+            })
+            .observe(SECRETS_MANAGER, "PutResourcePolicy", config, self)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn delete_resource_policy(self, config: &SdkConfig) -> Result<()> {
+        let client = Client::new(config);
+
+        client
+            .delete_resource_policy()
+            .secret_id(self)
+            .send()
+            .map_err(|e| {
+                Error::DeleteResourcePolicy {
+                    secret_name: self.to_owned(),
+                    source: e,
+                }
+                .or_timeout(Some(self))
+            })
+            .observe(SECRETS_MANAGER, "DeleteResourcePolicy", config, self)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn validate_resource_policy(
+        self,
+        config: &SdkConfig,
+        policy: &Value,
+    ) -> Result<PolicyValidation> {
+        let client = Client::new(config);
+
+        let resp = client
+            .validate_resource_policy()
+            .set_secret_id(if self.is_empty() {
+                None
+            } else {
+                Some(self.to_owned())
+            })
+            .resource_policy(policy.to_string())
+            .send()
+            .map_err(|e| {
+                Error::ValidateResourcePolicy {
+                    secret_name: self.to_owned(),
+                    source: e,
+                }
+                .or_timeout(Some(self))
+            })
+            .observe(SECRETS_MANAGER, "ValidateResourcePolicy", config, self)
+            .await?;
+
+        Ok(PolicyValidation {
+            passed: resp.policy_validation_passed,
+            errors: resp
+                .validation_errors
+                .unwrap_or_default()
+                .into_iter()
+                .map(|e| PolicyValidationError {
+                    check_name: e.check_name,
+                    message: e.error_message,
+                })
+                .collect(),
+        })
+    }
+
+    async fn replicate_secret_to_regions(
+        self,
+        config: &SdkConfig,
+        regions: &[ReplicaRegion],
+        force_overwrite: bool,
+    ) -> Result<Vec<ReplicationStatus>> {
+        let client = Client::new(config);
+
+        let resp = client
+            .replicate_secret_to_regions()
+            .secret_id(self)
+            .set_add_replica_regions(Some(regions.iter().map(Into::into).collect()))
+            .force_overwrite_replica_secret(force_overwrite)
+            .send()
+            .map_err(|e| {
+                Error::ReplicateSecret {
+                    secret_name: self.to_owned(),
+                    source: e,
+                }
+                .or_timeout(Some(self))
+            })
+            .observe(SECRETS_MANAGER, "ReplicateSecretToRegions", config, self)
+            .await?;
+
+        Ok(resp
+            .replication_status
+            .unwrap_or_default()
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    async fn remove_regions_from_replication(
+        self,
+        config: &SdkConfig,
+        regions: &[&str],
+    ) -> Result<Vec<ReplicationStatus>> {
+        let client = Client::new(config);
+
+        let resp = client
+            .remove_regions_from_replication()
+            .secret_id(self)
+            .set_remove_replica_regions(Some(regions.iter().map(|r| r.to_string()).collect()))
+            .send()
+            .map_err(|e| {
+                Error::RemoveReplicaRegions {
+                    secret_name: self.to_owned(),
+                    source: e,
+                }
+                .or_timeout(Some(self))
+            })
+            .observe(
+                SECRETS_MANAGER,
+                "RemoveRegionsFromReplication",
+                config,
+                self,
+            )
+            .await?;
+
+        Ok(resp
+            .replication_status
+            .unwrap_or_default()
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    async fn stop_replication_to_replica(self, config: &SdkConfig) -> Result<()> {
+        let client = Client::new(config);
+
+        client
+            .stop_replication_to_replica()
+            .secret_id(self)
+            .send()
+            .map_err(|e| {
+                Error::StopReplication {
+                    secret_name: self.to_owned(),
+                    source: e,
+                }
+                .or_timeout(Some(self))
+            })
+            .observe(SECRETS_MANAGER, "StopReplicationToReplica", config, self)
+            .await?;
+
+        Ok(())
     }
 }
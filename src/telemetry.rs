@@ -0,0 +1,326 @@
+//! Instrumentation of requests to AWS.
+//!
+//! With the `tracing` feature enabled, each request runs in a span which
+//! records the service, operation, resource name, region, attempt, latency
+//! and error kind. Reads which are tried in several regions run in a parent
+//! span, which records the number of attempts; each request span records
+//! which attempt it is (`1` for a read which isn't retried in another
+//! region). Retries made by the SDK within a request are not counted, as the
+//! SDK doesn't report them. Lookups of values kept warm by a `Refresher` are
+//! traced too, with a `cache.hit` field.
+//!
+//! With the `metrics` feature enabled, each request is also counted (by
+//! outcome, and by error kind when it fails) and timed, using the `metrics`
+//! facade; see [`REQUESTS`], [`ERRORS`] and [`LATENCY`]. Lookups of values
+//! kept warm by a `Refresher` are counted too, as cache hits or misses.
+//!
+//! Only this metadata is recorded: the output of a request is opaque here
+//! (there is no `Debug` bound on it), and errors are recorded by their AWS
+//! error code rather than their message, which could echo a value.
+
+#[cfg(feature = "tracing")]
+use std::cell::Cell;
+use std::future::Future;
+#[cfg(feature = "metrics")]
+use std::time::Duration;
+#[cfg(any(feature = "tracing", feature = "metrics"))]
+use std::time::Instant;
+
+use aws_config::SdkConfig;
+#[cfg(any(feature = "tracing", feature = "metrics"))]
+use futures_util::future::BoxFuture;
+
+use crate::Result;
+
+/// Name of the AWS Secrets Manager service.
+#[cfg(feature = "sm")]
+pub(crate) const SECRETS_MANAGER: &str = "secretsmanager";
+
+/// Name of the SSM service.
+#[cfg(feature = "params")]
+pub(crate) const SSM: &str = "ssm";
+
+/// Counter of requests, labeled by `service`, `operation` and `outcome`
+/// (`ok` or `error`).
+#[cfg(feature = "metrics")]
+pub(crate) const REQUESTS: &str = "aws_secrets_requests_total";
+
+/// Counter of failed requests, labeled by `service`, `operation` and `kind`
+/// (the AWS error code, or `Timeout`, `Network` or `Other`).
+#[cfg(feature = "metrics")]
+pub(crate) const ERRORS: &str = "aws_secrets_errors_total";
+
+/// Histogram of request latencies in seconds, labeled by `service` and
+/// `operation`.
+#[cfg(feature = "metrics")]
+pub(crate) const LATENCY: &str = "aws_secrets_request_duration_seconds";
+
+/// Counter of lookups of values kept warm by a `Refresher` (with
+/// `Refresher::secret` or `Refresher::parameter`), labeled by `kind`
+/// (`secret` or `parameter`) and `cache`: `hit` when the value is kept warm,
+/// or `miss` when it isn't, so it has to be fetched from AWS.
+#[cfg(all(feature = "metrics", feature = "refresh"))]
+pub(crate) const CACHE: &str = "aws_secrets_cache_lookups_total";
+
+/// Counter of lookups through a `CoalescingStore`, labeled by `operation`
+/// and `shared`: `true` when the lookup joined an in-flight request, or
+/// `false` when it sent one.
+#[cfg(all(feature = "metrics", feature = "sm", feature = "params"))]
+pub(crate) const COALESCED: &str = "aws_secrets_coalesced_lookups_total";
+
+/// Extension trait which instruments a request.
+pub(crate) trait Observe<'a, T: 'a>: Future<Output = Result<T>> + Send + Sized + 'a {
+    /// Runs the request, recording it as `operation` on `resource`.
+    #[cfg(any(feature = "tracing", feature = "metrics"))]
+    fn observe(
+        self,
+        service: &'static str,
+        operation: &'static str,
+        config: &'a SdkConfig,
+        resource: &'a str,
+    ) -> BoxFuture<'a, Result<T>> {
+        Box::pin(observe(service, operation, config, resource, self))
+    }
+
+    /// Returns the request as is, as there is nothing to record.
+    #[cfg(not(any(feature = "tracing", feature = "metrics")))]
+    fn observe(
+        self,
+        _service: &'static str,
+        _operation: &'static str,
+        _config: &'a SdkConfig,
+        _resource: &'a str,
+    ) -> Self {
+        self
+    }
+}
+
+impl<'a, T: 'a, F> Observe<'a, T> for F where F: Future<Output = Result<T>> + Send + 'a {}
+
+#[cfg(any(feature = "tracing", feature = "metrics"))]
+async fn observe<T, F>(
+    service: &'static str,
+    operation: &'static str,
+    config: &SdkConfig,
+    resource: &str,
+    request: F,
+) -> Result<T>
+where
+    F: Future<Output = Result<T>>,
+{
+    #[cfg(feature = "metrics")]
+    let start = Instant::now();
+
+    let result = traced(service, operation, config, resource, request).await;
+
+    #[cfg(feature = "metrics")]
+    record_metrics(service, operation, start.elapsed(), &result);
+
+    result
+}
+
+#[cfg(feature = "tracing")]
+async fn traced<T, F>(
+    service: &'static str,
+    operation: &'static str,
+    config: &SdkConfig,
+    resource: &str,
+    request: F,
+) -> Result<T>
+where
+    F: Future<Output = Result<T>>,
+{
+    use tracing::field::Empty;
+    use tracing::Instrument;
+
+    let span = tracing::info_span!(
+        "aws_secrets",
+        service,
+        operation,
+        resource,
+        region = config.region().map(|r| r.as_ref()),
+        attempt = ATTEMPT.with(Cell::get),
+        latency_ms = Empty,
+        error = Empty,
+    );
+
+    let start = Instant::now();
+    let result = request.instrument(span.clone()).await;
+    span.record("latency_ms", start.elapsed().as_millis() as u64);
+    if let Err(e) = &result {
+        span.record("error", error_kind(e));
+    }
+
+    result
+}
+
+#[cfg(all(feature = "metrics", not(feature = "tracing")))]
+async fn traced<T, F>(
+    _service: &'static str,
+    _operation: &'static str,
+    _config: &SdkConfig,
+    _resource: &str,
+    request: F,
+) -> Result<T>
+where
+    F: Future<Output = Result<T>>,
+{
+    request.await
+}
+
+#[cfg(feature = "metrics")]
+fn record_metrics<T>(
+    service: &'static str,
+    operation: &'static str,
+    latency: Duration,
+    result: &Result<T>,
+) {
+    let outcome = if result.is_ok() { "ok" } else { "error" };
+    metrics::increment_counter!(
+        REQUESTS,
+        "service" => service,
+        "operation" => operation,
+        "outcome" => outcome,
+    );
+    metrics::histogram!(
+        LATENCY,
+        latency.as_secs_f64(),
+        "service" => service,
+        "operation" => operation,
+    );
+    if let Err(e) = result {
+        metrics::increment_counter!(
+            ERRORS,
+            "service" => service,
+            "operation" => operation,
+            "kind" => error_kind(e).to_owned(),
+        );
+    }
+}
+
+/// Records a lookup of a value kept warm by a `Refresher`, which either is
+/// kept warm (a hit), or isn't (a miss).
+#[cfg(all(feature = "metrics", feature = "refresh"))]
+pub(crate) fn record_cache_lookup(kind: &'static str, hit: bool) {
+    let cache = if hit { "hit" } else { "miss" };
+    metrics::increment_counter!(CACHE, "kind" => kind, "cache" => cache);
+}
+
+/// Traces whether a lookup of `resource` joined an in-flight request, or sent
+/// one.
+#[cfg(all(feature = "sm", feature = "params", feature = "tracing"))]
+pub(crate) fn trace_coalesced(operation: &'static str, resource: &str, shared: bool) {
+    tracing::debug!(operation, resource, shared, "coalesced lookup");
+}
+
+/// Counts a lookup which either joined an in-flight request, or sent one.
+#[cfg(all(feature = "sm", feature = "params", feature = "metrics"))]
+pub(crate) fn count_coalesced(operation: &'static str, shared: bool) {
+    metrics::increment_counter!(
+        COALESCED,
+        "operation" => operation,
+        "shared" => if shared { "true" } else { "false" },
+    );
+}
+
+/// A span around a read which is tried in several regions, in turn.
+#[derive(Debug)]
+pub(crate) struct Attempts {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
+
+impl Attempts {
+    /// Opens a span for a read, e.g. by a `RegionFailover`.
+    #[cfg(feature = "tracing")]
+    pub(crate) fn start(kind: &'static str) -> Self {
+        Self {
+            span: tracing::info_span!("aws_secrets", kind, attempts = 0_usize),
+        }
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    pub(crate) fn start(_kind: &'static str) -> Self {
+        Self {}
+    }
+
+    /// Runs one attempt of the read, as attempt number `attempt`, which the
+    /// spans of its requests record.
+    #[cfg(feature = "tracing")]
+    pub(crate) async fn run<F: Future>(&self, attempt: usize, read: F) -> F::Output {
+        use tracing::Instrument;
+
+        self.span.record("attempts", attempt);
+        let read = read.instrument(self.span.clone());
+        futures_util::pin_mut!(read);
+        futures_util::future::poll_fn(|cx| {
+            let _attempt = AttemptGuard::set(attempt);
+            read.as_mut().poll(cx)
+        })
+        .await
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    pub(crate) async fn run<F: Future>(&self, _attempt: usize, read: F) -> F::Output {
+        read.await
+    }
+}
+
+#[cfg(feature = "tracing")]
+thread_local! {
+    /// The attempt of the read which is being polled on this thread.
+    static ATTEMPT: Cell<usize> = Cell::new(1);
+}
+
+/// Sets the current attempt while a read is polled, and restores the
+/// previous one when dropped.
+#[cfg(feature = "tracing")]
+struct AttemptGuard(usize);
+
+#[cfg(feature = "tracing")]
+impl AttemptGuard {
+    fn set(attempt: usize) -> Self {
+        Self(ATTEMPT.with(|current| current.replace(attempt)))
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl Drop for AttemptGuard {
+    fn drop(&mut self) {
+        ATTEMPT.with(|current| current.set(self.0));
+    }
+}
+
+/// Traces a lookup of a value kept warm by a `Refresher`, as a span which
+/// records whether the value is kept warm (`cache.hit`).
+#[cfg(all(feature = "tracing", feature = "refresh"))]
+pub(crate) fn trace_cache_lookup(kind: &'static str, resource: &str, hit: bool) {
+    let _span = tracing::info_span!("aws_secrets", kind, resource, cache.hit = hit).entered();
+}
+
+/// Returns a short description of an error, which never includes a value.
+#[cfg(any(feature = "tracing", feature = "metrics"))]
+fn error_kind(error: &crate::Error) -> &str {
+    if error.is_timeout() {
+        "Timeout"
+    } else if let Some(code) = error.code() {
+        code
+    } else if error.is_transient() {
+        "Network"
+    } else {
+        "Other"
+    }
+}
+
+#[cfg(all(test, any(feature = "tracing", feature = "metrics"), feature = "sm"))]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    #[test]
+    fn error_kind_never_includes_the_message() {
+        let err = serde_json::from_str::<u32>(r#""hunter2""#).unwrap_err();
+
+        assert_eq!(error_kind(&Error::DeserializeError(err)), "Other");
+    }
+}
@@ -0,0 +1,178 @@
+//! Render config templates that reference secrets and parameters.
+//!
+//! A placeholder is written as `{{ <reference> }}`, where the reference is
+//! any string accepted by [`SecretRef`], most commonly the short forms
+//! `{{ sm:<secret-id> }}` and `{{ ssm:<parameter-name> }}`. A placeholder
+//! may select a field of a JSON value with `#`, for example
+//! `{{ sm:prod/db#password }}`. Placeholders which don't look like a
+//! reference are left untouched, so templates can still contain e.g.
+//! Helm or Jinja syntax.
+//!
+//! All secrets and parameters referenced by a template are fetched
+//! concurrently, and each of them is fetched only once.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Range;
+use std::path::Path;
+
+use aws_config::SdkConfig;
+use futures_util::future::try_join_all;
+
+use crate::secret_ref::{SecretRef, Source};
+use crate::{Error, Result};
+
+const OPEN: &str = "{{";
+const CLOSE: &str = "}}";
+
+/// Prefixes of the placeholders this module replaces.
+const PREFIXES: &[&str] = &["sm:", "ssm:", "secretsmanager:", "resolve:"];
+
+/// A placeholder found in a template.
+#[derive(Debug, PartialEq, Eq)]
+struct Placeholder {
+    /// Byte range of the placeholder (including the braces) in the template.
+    range: Range<usize>,
+    /// The secret or parameter to retrieve.
+    reference: SecretRef,
+}
+
+/// Renders a template, replacing each placeholder with the value
+/// of the secret or parameter it references.
+pub async fn render(template: &str, config: &SdkConfig) -> Result<String> {
+    let placeholders = parse(template)?;
+
+    let sources: BTreeSet<&Source> = placeholders.iter().map(|p| &p.reference.source).collect();
+    let values = try_join_all(
+        sources
+            .into_iter()
+            .map(|source| async move { Ok::<_, Error>((source, source.fetch(config).await?)) }),
+    )
+    .await?;
+    let values: BTreeMap<_, _> = values.into_iter().collect();
+
+    let mut out = String::with_capacity(template.len());
+    let mut last = 0;
+
+    for p in &placeholders {
+        out.push_str(&template[last..p.range.start]);
+        let value = values[&p.reference.source].clone();
+        out.push_str(&p.reference.select(value)?);
+        last = p.range.end;
+    }
+    out.push_str(&template[last..]);
+
+    Ok(out)
+}
+
+/// Reads a template from a file and renders it; see [`render`].
+pub async fn render_file(path: impl AsRef<Path>, config: &SdkConfig) -> Result<String> {
+    let template = std::fs::read_to_string(path)?;
+    render(&template, config).await
+}
+
+/// Finds all placeholders which reference a secret or parameter, in order.
+fn parse(template: &str) -> Result<Vec<Placeholder>> {
+    let mut placeholders = Vec::new();
+    let mut pos = 0;
+
+    while let Some(offset) = template[pos..].find(OPEN) {
+        let start = pos + offset;
+        let inner_start = start + OPEN.len();
+
+        let inner_end = match template[inner_start..].find(CLOSE) {
+            Some(offset) => inner_start + offset,
+            None => {
+                if is_reference(&template[inner_start..]) {
+                    return Err(template_error(template, start, "unterminated placeholder"));
+                }
+                break;
+            }
+        };
+        let end = inner_end + CLOSE.len();
+        let inner = &template[inner_start..inner_end];
+
+        if is_reference(inner) {
+            // `{{resolve:...}}` is parsed with its braces, the short forms without.
+            let text = if inner.trim_start().starts_with("resolve:") {
+                &template[start..end]
+            } else {
+                inner
+            };
+            let reference = text.parse().map_err(|e| match e {
+                Error::InvalidRef { message, .. } => template_error(template, start, &message),
+                other => other,
+            })?;
+            placeholders.push(Placeholder {
+                range: start..end,
+                reference,
+            });
+        }
+
+        pos = end;
+    }
+
+    Ok(placeholders)
+}
+
+/// Returns true if the text after `{{` looks like a secret reference.
+fn is_reference(text: &str) -> bool {
+    let text = text.trim_start();
+    PREFIXES.iter().any(|prefix| text.starts_with(prefix))
+}
+
+/// Builds an [`Error::Template`] for the placeholder at the given byte offset.
+fn template_error(template: &str, offset: usize, message: &str) -> Error {
+    let line = template[..offset].matches('\n').count() + 1;
+    Error::Template {
+        line,
+        message: message.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_placeholders() {
+        let template =
+            "user={{ sm:prod/db#username }}\nurl={{ssm:/app/api_url}}\nname={{ .Values.name }}";
+        let placeholders = parse(template).unwrap();
+
+        assert_eq!(
+            placeholders,
+            vec![
+                Placeholder {
+                    range: 5..30,
+                    reference: "sm:prod/db#username".parse().unwrap(),
+                },
+                Placeholder {
+                    range: 35..55,
+                    reference: "ssm:/app/api_url".parse().unwrap(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_dynamic_references() {
+        let template = "key: {{resolve:ssm-secure:/app/key:3}}";
+        let placeholders = parse(template).unwrap();
+
+        assert_eq!(placeholders.len(), 1);
+        assert_eq!(placeholders[0].range, 5..template.len());
+        assert_eq!(
+            placeholders[0].reference.to_string(),
+            "ssm:///app/key?version=3"
+        );
+    }
+
+    #[test]
+    fn reports_line_of_bad_placeholder() {
+        let err = parse("a=1\nb={{ sm:prod/db#password").unwrap_err();
+        assert!(matches!(err, Error::Template { line: 2, .. }));
+
+        let err = parse("{{ ssm: }}").unwrap_err();
+        assert!(matches!(err, Error::Template { line: 1, .. }));
+    }
+}
@@ -0,0 +1,256 @@
+//! Test support: run operations against canned HTTP responses.
+//!
+//! [`ReplayConfig`] builds an `SdkConfig` whose HTTP connector replays a
+//! list of responses (in order) instead of calling AWS, so that both
+//! successful and failing calls can be tested without network access or
+//! credentials.
+//!
+//! ```no_run
+//! use aws_secrets::test_util::{responses, ReplayConfig};
+//! use aws_secrets::SSMParamExt;
+//!
+//! # async fn run() {
+//! let (config, replay) = ReplayConfig::new()
+//!     .respond(responses::parameter("/my/list", "a,b,c", "StringList"))
+//!     .respond(responses::parameter_not_found())
+//!     .build();
+//!
+//! let values = "/my/list".get_string_list(&config).await.unwrap();
+//! assert_eq!(values, ["a", "b", "c"]);
+//!
+//! let err = "/my/missing".get_string(&config).await.unwrap_err();
+//! assert!(err.is_not_found());
+//!
+//! assert_eq!(replay.requests()[1].target, "AmazonSSM.GetParameter");
+//! # }
+//! ```
+
+use aws_config::retry::RetryConfig;
+use aws_config::SdkConfig;
+use aws_credential_types::provider::SharedCredentialsProvider;
+use aws_credential_types::Credentials;
+use aws_smithy_async::rt::sleep::{SharedAsyncSleep, TokioSleep};
+use aws_smithy_client::erase::DynConnector;
+use aws_smithy_client::test_connection::TestConnection;
+use aws_smithy_http::body::SdkBody;
+use aws_types::region::Region;
+
+/// A canned HTTP response.
+pub type Response = http::Response<SdkBody>;
+
+/// Builds an `SdkConfig` which replays canned responses.
+#[derive(Debug)]
+pub struct ReplayConfig {
+    responses: Vec<Response>,
+    region: String,
+    max_attempts: u32,
+}
+
+/// A request which was sent to the replay connector.
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    /// The operation, from the `X-Amz-Target` header (e.g. `secretsmanager.GetSecretValue`).
+    pub target: String,
+    /// The JSON request body.
+    pub body: serde_json::Value,
+}
+
+/// Handle to the replay connector, for inspecting the requests it received.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    connection: TestConnection<SdkBody>,
+}
+
+impl Default for ReplayConfig {
+    fn default() -> Self {
+        Self {
+            responses: Vec::new(),
+            region: "us-east-1".to_owned(),
+            max_attempts: 1,
+        }
+    }
+}
+
+impl ReplayConfig {
+    /// Creates a config with no responses, in the `us-east-1` region,
+    /// and with retries disabled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the region of the `SdkConfig`.
+    pub fn region(mut self, region: impl Into<String>) -> Self {
+        self.region = region.into();
+        self
+    }
+
+    /// Sets the maximum number of attempts per operation (including the
+    /// first one), to test retries of e.g. throttled requests.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Adds a response to replay. Responses are replayed in the order
+    /// they are added, one per HTTP request.
+    pub fn respond(mut self, response: Response) -> Self {
+        self.responses.push(response);
+        self
+    }
+
+    /// Builds the `SdkConfig`, and a handle to inspect the requests it sends.
+    pub fn build(self) -> (SdkConfig, Replay) {
+        let events = self
+            .responses
+            .into_iter()
+            .map(|response| {
+                let request = http::Request::builder()
+                    .body(SdkBody::empty())
+                    .expect("valid request");
+                (request, response)
+            })
+            .collect();
+        let connection = TestConnection::new(events);
+
+        let retry_config = match self.max_attempts {
+            0 | 1 => RetryConfig::disabled(),
+            n => RetryConfig::standard().with_max_attempts(n),
+        };
+
+        let config = SdkConfig::builder()
+            .region(Region::new(self.region))
+            .credentials_provider(SharedCredentialsProvider::new(Credentials::new(
+                "AKIDTEST", "test", None, None, "replay",
+            )))
+            .http_connector(DynConnector::new(connection.clone()))
+            .sleep_impl(SharedAsyncSleep::new(TokioSleep::new()))
+            .retry_config(retry_config)
+            .build();
+
+        (config, Replay { connection })
+    }
+}
+
+impl Replay {
+    /// Returns the requests received so far, in order.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.connection
+            .requests()
+            .iter()
+            .map(|r| RecordedRequest {
+                target: r
+                    .actual
+                    .headers()
+                    .get("x-amz-target")
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or_default()
+                    .to_owned(),
+                body: serde_json::from_slice(r.actual.body()).unwrap_or_default(),
+            })
+            .collect()
+    }
+}
+
+/// Canned responses of the AWS JSON protocol used by Secrets Manager and SSM.
+pub mod responses {
+    use serde_json::{json, Value};
+
+    use super::Response;
+    use aws_smithy_http::body::SdkBody;
+
+    /// A response with the given status code and JSON body.
+    pub fn json(status: u16, body: Value) -> Response {
+        http::Response::builder()
+            .status(status)
+            .header("content-type", "application/x-amz-json-1.1")
+            .body(SdkBody::from(body.to_string()))
+            .expect("valid response")
+    }
+
+    /// An error response with the given status code, error code and message.
+    pub fn error(status: u16, code: &str, message: &str) -> Response {
+        json(status, json!({ "__type": code, "Message": message }))
+    }
+
+    /// A successful `secretsmanager:GetSecretValue` response.
+    pub fn secret_string(secret_name: &str, value: &str) -> Response {
+        json(
+            200,
+            json!({
+                "ARN": format!("arn:aws:secretsmanager:us-east-1:123456789012:secret:{secret_name}-AbCdEf"),
+                "Name": secret_name,
+                "VersionId": "EXAMPLE1-90ab-cdef-fedc-ba987EXAMPLE",
+                "SecretString": value,
+                "VersionStages": ["AWSCURRENT"],
+                "CreatedDate": 1523477145.713,
+            }),
+        )
+    }
+
+    /// A successful `secretsmanager:DescribeSecret` response, for a secret with
+    /// rotation enabled and the given versions and their staging labels.
+    pub fn describe_secret(secret_name: &str, versions: &[(&str, &[&str])]) -> Response {
+        let versions: serde_json::Map<String, Value> = versions
+            .iter()
+            .map(|(id, stages)| (id.to_string(), json!(stages)))
+            .collect();
+        json(
+            200,
+            json!({
+                "ARN": format!("arn:aws:secretsmanager:us-east-1:123456789012:secret:{secret_name}-AbCdEf"),
+                "Name": secret_name,
+                "RotationEnabled": true,
+                "VersionIdsToStages": versions,
+                "CreatedDate": 1523477145.713,
+            }),
+        )
+    }
+
+    /// A successful `ssm:GetParameter` response, for a parameter of the given
+    /// data type (`String`, `StringList` or `SecureString`).
+    pub fn parameter(name: &str, value: &str, data_type: &str) -> Response {
+        json(
+            200,
+            json!({
+                "Parameter": {
+                    "Name": name,
+                    "Type": data_type,
+                    "Value": value,
+                    "Version": 1,
+                    "ARN": format!("arn:aws:ssm:us-east-1:123456789012:parameter{name}"),
+                    "DataType": "text",
+                    "LastModifiedDate": 1523477145.713,
+                }
+            }),
+        )
+    }
+
+    /// A `ResourceNotFoundException` error, as returned by Secrets Manager.
+    pub fn secret_not_found() -> Response {
+        error(
+            400,
+            "ResourceNotFoundException",
+            "Secrets Manager can't find the specified secret.",
+        )
+    }
+
+    /// A `ParameterNotFound` error, as returned by SSM.
+    pub fn parameter_not_found() -> Response {
+        error(400, "ParameterNotFound", "")
+    }
+
+    /// A `ThrottlingException` error.
+    pub fn throttling() -> Response {
+        error(400, "ThrottlingException", "Rate exceeded")
+    }
+
+    /// An empty `500 Internal Server Error` response.
+    pub fn internal_error() -> Response {
+        json(500, Value::Object(Default::default()))
+    }
+
+    /// An empty successful response, for operations whose output is not inspected.
+    pub fn ok() -> Response {
+        json(200, Value::Object(Default::default()))
+    }
+}
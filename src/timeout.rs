@@ -0,0 +1,110 @@
+//! Timeouts for operations on secrets and parameters.
+//!
+//! [`Timeouts`] can be applied to the shared config once, so that they hold
+//! for every operation (as with the timeout settings of
+//! [`ConfigBuilder`](crate::ConfigBuilder)), or to a copy of it for a
+//! single call. Settings which are not set are kept from the config.
+//!
+//! When an operation times out, it fails with [`Error::Timeout`], which
+//! names the secret or parameter. Operations can also be cancelled by
+//! dropping their future; for writes, the change may or may not have been
+//! applied.
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use aws_secrets::timeout::Timeouts;
+//! use aws_secrets::SSMParamExt;
+//!
+//! # async fn run() -> aws_secrets::Result<()> {
+//! let config = Timeouts::new()
+//!     .connect(Duration::from_secs(1))
+//!     .read(Duration::from_secs(2))
+//!     .apply(&aws_secrets::config_from_env().await);
+//!
+//! // Allow at most five seconds (including retries) for this call.
+//! let startup = Timeouts::new().total(Duration::from_secs(5)).apply(&config);
+//! let url = "/my-app/db-url".get_string(&startup).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Error::Timeout`]: crate::Error::Timeout
+
+use std::time::Duration;
+
+use aws_config::timeout::TimeoutConfig;
+use aws_config::SdkConfig;
+
+/// Connect, read and total timeouts; see the [module docs](self).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Timeouts {
+    connect: Option<Duration>,
+    read: Option<Duration>,
+    total: Option<Duration>,
+}
+
+impl Timeouts {
+    /// Creates an empty set of timeouts, which keeps those of the config.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the timeout for establishing a connection.
+    pub fn connect(mut self, timeout: Duration) -> Self {
+        self.connect = Some(timeout);
+        self
+    }
+
+    /// Sets the timeout for reading the first byte of a response.
+    pub fn read(mut self, timeout: Duration) -> Self {
+        self.read = Some(timeout);
+        self
+    }
+
+    /// Sets the timeout for a whole operation, including retries.
+    pub fn total(mut self, timeout: Duration) -> Self {
+        self.total = Some(timeout);
+        self
+    }
+
+    /// Returns a copy of `config` with these timeouts.
+    pub fn apply(&self, config: &SdkConfig) -> SdkConfig {
+        config
+            .to_builder()
+            .timeout_config(self.timeout_config(config.timeout_config()))
+            .build()
+    }
+
+    fn timeout_config(&self, current: Option<&TimeoutConfig>) -> TimeoutConfig {
+        let mut builder = TimeoutConfig::builder();
+        builder
+            .set_connect_timeout(self.connect)
+            .set_read_timeout(self.read)
+            .set_operation_timeout(self.total);
+        match current {
+            Some(current) => builder.take_unset_from(current.to_builder()).build(),
+            None => builder.build(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_unset_timeouts() {
+        let current = TimeoutConfig::builder()
+            .connect_timeout(Duration::from_secs(3))
+            .operation_timeout(Duration::from_secs(30))
+            .build();
+
+        let config = Timeouts::new()
+            .total(Duration::from_secs(5))
+            .timeout_config(Some(&current));
+
+        assert_eq!(config.connect_timeout(), Some(Duration::from_secs(3)));
+        assert_eq!(config.read_timeout(), None);
+        assert_eq!(config.operation_timeout(), Some(Duration::from_secs(5)));
+    }
+}
@@ -0,0 +1,47 @@
+//! Helpers shared by the Secrets Manager and SSM modules.
+
+use std::future::Future;
+use std::time::SystemTime;
+
+use futures_util::stream::{self, BoxStream, StreamExt, TryStreamExt};
+
+use crate::Result;
+
+/// Converts an SDK timestamp to a `SystemTime`.
+pub(crate) fn to_system_time<D>(date: Option<D>) -> Option<SystemTime>
+where
+    SystemTime: TryFrom<D>,
+{
+    date.and_then(|date| SystemTime::try_from(date).ok())
+}
+
+/// Returns a stream of the items of every page of a paginated operation.
+///
+/// `fetch_page` sends the request for the page with the given token (`None`
+/// for the first page), and returns its items and the token of the next page.
+pub(crate) fn paginate<T, F, Fut>(mut fetch_page: F) -> BoxStream<'static, Result<T>>
+where
+    T: Send + 'static,
+    F: FnMut(Option<String>) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<(Vec<T>, Option<String>)>> + Send + 'static,
+{
+    // The state is the token of the next page to fetch, or `None` once the
+    // last page has been fetched.
+    let pages = stream::try_unfold(Some(None), move |next_token: Option<Option<String>>| {
+        let page = next_token.map(&mut fetch_page);
+        async move {
+            match page {
+                Some(page) => {
+                    let (items, next_token) = page.await?;
+                    Ok(Some((items, next_token.map(Some))))
+                }
+                None => Ok(None),
+            }
+        }
+    });
+
+    pages
+        .map_ok(|items| stream::iter(items.into_iter().map(Ok)))
+        .try_flatten()
+        .boxed()
+}
@@ -0,0 +1,118 @@
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use serde_json::json;
+
+use aws_secrets::changes::watch_secret;
+use aws_secrets::test_util::{responses, Replay, ReplayConfig, Response};
+
+const V1: &str = "EXAMPLE1-90ab-cdef-fedc-ba987EXAMPLE";
+const V2: &str = "EXAMPLE2-90ab-cdef-fedc-ba987EXAMPLE";
+
+/// A `GetSecretValue` response for the given version.
+fn secret_version(version_id: &str, value: &str) -> Response {
+    responses::json(
+        200,
+        json!({
+            "Name": "my-secret",
+            "VersionId": version_id,
+            "SecretString": value,
+            "VersionStages": ["AWSCURRENT"],
+        }),
+    )
+}
+
+fn current(version_id: &str) -> Response {
+    responses::describe_secret("my-secret", &[(version_id, &["AWSCURRENT"])])
+}
+
+fn targets(replay: &Replay) -> Vec<String> {
+    replay.requests().into_iter().map(|r| r.target).collect()
+}
+
+#[tokio::test]
+async fn first_poll_emits_the_baseline() {
+    let (config, replay) = ReplayConfig::new()
+        .respond(current(V1))
+        .respond(secret_version(V1, "first"))
+        .build();
+    let mut changes = Box::pin(watch_secret(config, "my-secret", Duration::from_millis(1)));
+
+    let change = changes.next().await.unwrap().unwrap();
+
+    assert_eq!(change.secret_id, "my-secret");
+    assert_eq!(change.old_version, None);
+    assert_eq!(change.new_version, V1);
+    assert_eq!(change.value, "first");
+    let requests = replay.requests();
+    assert_eq!(requests[1].target, "secretsmanager.GetSecretValue");
+    assert_eq!(requests[1].body["VersionId"], V1);
+}
+
+#[tokio::test]
+async fn changed_version_emits_a_change() {
+    let (config, _) = ReplayConfig::new()
+        .respond(current(V1))
+        .respond(secret_version(V1, "first"))
+        .respond(current(V2))
+        .respond(secret_version(V2, "second"))
+        .build();
+    let mut changes = Box::pin(watch_secret(config, "my-secret", Duration::from_millis(1)));
+
+    changes.next().await.unwrap().unwrap();
+    let change = changes.next().await.unwrap().unwrap();
+
+    assert_eq!(change.old_version.as_deref(), Some(V1));
+    assert_eq!(change.new_version, V2);
+    assert_eq!(change.value, "second");
+}
+
+#[tokio::test]
+async fn unchanged_version_emits_nothing() {
+    let (config, replay) = ReplayConfig::new()
+        .respond(current(V1))
+        .respond(secret_version(V1, "first"))
+        .respond(current(V1))
+        .respond(current(V1))
+        .respond(current(V2))
+        .respond(secret_version(V2, "second"))
+        .build();
+    let mut changes = Box::pin(watch_secret(config, "my-secret", Duration::from_millis(1)));
+
+    changes.next().await.unwrap().unwrap();
+    let change = changes.next().await.unwrap().unwrap();
+
+    assert_eq!(change.old_version.as_deref(), Some(V1));
+    assert_eq!(change.new_version, V2);
+    assert_eq!(
+        targets(&replay),
+        [
+            "secretsmanager.DescribeSecret",
+            "secretsmanager.GetSecretValue",
+            "secretsmanager.DescribeSecret",
+            "secretsmanager.DescribeSecret",
+            "secretsmanager.DescribeSecret",
+            "secretsmanager.GetSecretValue",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn failed_poll_yields_an_error_and_continues() {
+    let (config, _) = ReplayConfig::new()
+        .respond(current(V1))
+        .respond(secret_version(V1, "first"))
+        .respond(responses::internal_error())
+        .respond(current(V2))
+        .respond(secret_version(V2, "second"))
+        .build();
+    let mut changes = Box::pin(watch_secret(config, "my-secret", Duration::from_millis(1)));
+
+    changes.next().await.unwrap().unwrap();
+    let err = changes.next().await.unwrap().unwrap_err();
+    assert!(err.is_transient(), "{:?}", err);
+    let change = changes.next().await.unwrap().unwrap();
+
+    assert_eq!(change.old_version.as_deref(), Some(V1));
+    assert_eq!(change.new_version, V2);
+}
@@ -0,0 +1,106 @@
+use metrics::{SharedString, Unit};
+use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+use metrics_util::CompositeKey;
+
+use aws_secrets::refresh::Refresher;
+use aws_secrets::store::MemoryStore;
+use aws_secrets::test_util::{responses, ReplayConfig};
+use aws_secrets::SecretsExt;
+
+/// A recorded series, as returned by `Snapshot::into_vec`.
+type Series = (CompositeKey, Option<Unit>, Option<SharedString>, DebugValue);
+
+/// Returns the value of the series with the given name and labels.
+fn series(snapshot: &[Series], name: &str, labels: &[(&str, &str)]) -> Option<DebugValue> {
+    snapshot
+        .iter()
+        .find(|(key, _, _, _)| {
+            let key = key.key();
+            let mut actual: Vec<_> = key.labels().map(|l| (l.key(), l.value())).collect();
+            let mut expected = labels.to_vec();
+            actual.sort();
+            expected.sort();
+            key.name() == name && actual == expected
+        })
+        .map(|(_, _, _, value)| value.clone())
+}
+
+// The recorder is global, so every check runs in a single test.
+#[tokio::test]
+async fn records_requests_errors_latency_and_cache_lookups() {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    recorder.install().unwrap();
+
+    let (config, _) = ReplayConfig::new()
+        .respond(responses::secret_string("my-creds", "hunter2"))
+        .respond(responses::secret_not_found())
+        .build();
+    "my-creds".get_secret_string(&config).await.unwrap();
+    "missing".get_secret_string(&config).await.unwrap_err();
+
+    let refresher = Refresher::builder(MemoryStore::new().with_secret("prod/db", "hunter2"))
+        .secret("prod/db")
+        .start()
+        .await
+        .unwrap();
+    let watch = refresher.secret("prod/db").unwrap();
+    // Reading the value again is not another lookup.
+    watch.get();
+    watch.get();
+    assert!(refresher.parameter("/not/registered").is_none());
+
+    let snapshot = snapshotter.snapshot().into_vec();
+    let request = [
+        ("service", "secretsmanager"),
+        ("operation", "GetSecretValue"),
+    ];
+    assert_eq!(
+        series(
+            &snapshot,
+            "aws_secrets_requests_total",
+            &[request[0], request[1], ("outcome", "ok")]
+        ),
+        Some(DebugValue::Counter(1))
+    );
+    assert_eq!(
+        series(
+            &snapshot,
+            "aws_secrets_requests_total",
+            &[request[0], request[1], ("outcome", "error")]
+        ),
+        Some(DebugValue::Counter(1))
+    );
+    assert_eq!(
+        series(
+            &snapshot,
+            "aws_secrets_errors_total",
+            &[
+                request[0],
+                request[1],
+                ("kind", "ResourceNotFoundException")
+            ]
+        ),
+        Some(DebugValue::Counter(1))
+    );
+    match series(&snapshot, "aws_secrets_request_duration_seconds", &request) {
+        Some(DebugValue::Histogram(latencies)) => assert_eq!(latencies.len(), 2),
+        other => panic!("unexpected latency series: {other:?}"),
+    }
+    assert_eq!(
+        series(
+            &snapshot,
+            "aws_secrets_cache_lookups_total",
+            &[("kind", "secret"), ("cache", "hit")]
+        ),
+        Some(DebugValue::Counter(1))
+    );
+    assert_eq!(
+        series(
+            &snapshot,
+            "aws_secrets_cache_lookups_total",
+            &[("kind", "parameter"), ("cache", "miss")]
+        ),
+        Some(DebugValue::Counter(1))
+    );
+}
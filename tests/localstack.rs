@@ -0,0 +1,234 @@
+//! Integration tests against a local stand-in for AWS, such as LocalStack.
+//!
+//! These are ignored by default. To run them:
+//!
+//! ```text
+//! docker run --rm -p 4566:4566 localstack/localstack
+//! cargo test --features all --test localstack -- --ignored
+//! ```
+//!
+//! The endpoint defaults to `http://localhost:4566`, and can be changed with
+//! the `AWS_SECRETS_ENDPOINT_URL` env var.
+
+use std::env;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use aws_sdk_secretsmanager::Client;
+use futures_util::{FutureExt, TryStreamExt};
+
+use aws_secrets::config::SdkConfig;
+use aws_secrets::{ConfigBuilder, SSMParamExt, SecretsExt, ENDPOINT_URL_ENV_VAR};
+
+const DEFAULT_ENDPOINT_URL: &str = "http://localhost:4566";
+
+async fn local_config() -> SdkConfig {
+    let url = env::var(ENDPOINT_URL_ENV_VAR).unwrap_or_else(|_| DEFAULT_ENDPOINT_URL.to_owned());
+    ConfigBuilder::new().local_endpoint(url).load().await
+}
+
+/// Returns a name which is unique across test runs, so that tests don't
+/// depend on the state left behind by earlier runs.
+fn unique_name(prefix: &str) -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    format!("{}-{}", prefix, nanos)
+}
+
+async fn create_secret(config: &SdkConfig, name: &str, value: &str) {
+    Client::new(config)
+        .create_secret()
+        .name(name)
+        .secret_string(value)
+        .send()
+        .await
+        .unwrap();
+}
+
+/// Deletes a secret, if it exists.
+async fn delete_secret(config: &SdkConfig, name: &str) {
+    // Best effort, as the test may have failed before creating it.
+    let _ = Client::new(config)
+        .delete_secret()
+        .secret_id(name)
+        .force_delete_without_recovery(true)
+        .send()
+        .await;
+}
+
+/// Deletes parameters, if they exist.
+async fn delete_parameters(config: &SdkConfig, names: &[&str]) {
+    // Best effort, as the test may have failed before creating them.
+    let _ = aws_sdk_ssm::Client::new(config)
+        .delete_parameters()
+        .set_names(Some(names.iter().map(|n| n.to_string()).collect()))
+        .send()
+        .await;
+}
+
+/// Runs `test`, then `cleanup` whether or not the test panicked, so that a
+/// failed run doesn't leave secrets or parameters behind.
+async fn cleaned_up(test: impl Future<Output = ()>, cleanup: impl Future<Output = ()>) {
+    let result = AssertUnwindSafe(test).catch_unwind().await;
+    cleanup.await;
+    if let Err(panic) = result {
+        panic::resume_unwind(panic);
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs a local endpoint, e.g. LocalStack"]
+async fn secret_round_trip() {
+    let config = local_config().await;
+    let name = unique_name("aws-secrets/round-trip");
+    cleaned_up(
+        async {
+            create_secret(&config, &name, r#"{"password":"first"}"#).await;
+
+            let value: serde_json::Value = name.as_str().get_secret(&config).await.unwrap();
+            assert_eq!(value["password"], "first");
+
+            name.as_str()
+                .put_secret_string(&config, r#"{"password":"second"}"#)
+                .await
+                .unwrap();
+            let value = name.as_str().get_secret_string(&config).await.unwrap();
+            assert_eq!(value, r#"{"password":"second"}"#);
+
+            name.as_str()
+                .set_tag(&config, "team", "core")
+                .await
+                .unwrap();
+            let description = name.as_str().describe_secret(&config).await.unwrap();
+            assert_eq!(description.name, name);
+            assert_eq!(
+                description.tags.get("team").map(String::as_str),
+                Some("core")
+            );
+        },
+        delete_secret(&config, &name),
+    )
+    .await;
+}
+
+#[tokio::test]
+#[ignore = "needs a local endpoint, e.g. LocalStack"]
+async fn list_secrets_by_prefix() {
+    let config = local_config().await;
+    let prefix = unique_name("aws-secrets/list");
+    let names = [format!("{}/a", prefix), format!("{}/b", prefix)];
+    cleaned_up(
+        async {
+            for name in &names {
+                create_secret(&config, name, "value").await;
+            }
+
+            let mut listed: Vec<String> = prefix
+                .as_str()
+                .list_secrets(&config)
+                .map_ok(|s| s.name)
+                .try_collect()
+                .await
+                .unwrap();
+            listed.sort();
+            assert_eq!(listed, names);
+        },
+        async {
+            for name in &names {
+                delete_secret(&config, name).await;
+            }
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+#[ignore = "needs a local endpoint, e.g. LocalStack"]
+async fn missing_secret_is_not_found() {
+    let config = local_config().await;
+    let name = unique_name("aws-secrets/missing");
+
+    let err = name.as_str().get_secret_string(&config).await.unwrap_err();
+    assert!(err.is_not_found(), "{:?}", err);
+}
+
+#[tokio::test]
+#[ignore = "needs a local endpoint, e.g. LocalStack"]
+async fn parameter_round_trip() {
+    let config = local_config().await;
+    let path = format!("/{}", unique_name("aws-secrets"));
+    let name = format!("{}/db/host", path);
+    let secure = format!("{}/db/password", path);
+    cleaned_up(
+        async {
+            name.as_str().put_string(&config, "db-1").await.unwrap();
+            name.as_str().put_string(&config, "db-2").await.unwrap();
+            assert_eq!(name.as_str().get_string(&config).await.unwrap(), "db-2");
+
+            secure
+                .as_str()
+                .put_secure_string(&config, "hunter2")
+                .await
+                .unwrap();
+            assert_eq!(
+                secure.as_str().get_secure_string(&config).await.unwrap(),
+                "hunter2"
+            );
+
+            let mut params = path.as_str().get_params_by_path(&config).await.unwrap();
+            params.sort();
+            assert_eq!(
+                params,
+                [
+                    (name.clone(), "db-2".to_owned()),
+                    (secure.clone(), "hunter2".to_owned()),
+                ]
+            );
+
+            let history = name.as_str().get_parameter_history(&config).await.unwrap();
+            let values: Vec<_> = history.iter().map(|v| v.value.as_str()).collect();
+            assert_eq!(values, ["db-1", "db-2"]);
+        },
+        delete_parameters(&config, &[&name, &secure]),
+    )
+    .await;
+}
+
+#[tokio::test]
+#[ignore = "needs a local endpoint, e.g. LocalStack"]
+async fn parameter_labels() {
+    let config = local_config().await;
+    let name = format!("/{}/feature", unique_name("aws-secrets"));
+    cleaned_up(
+        async {
+            name.as_str().put_string(&config, "off").await.unwrap();
+            name.as_str().put_string(&config, "on").await.unwrap();
+
+            let change = name
+                .as_str()
+                .label_parameter_version(&config, Some(1), &["stable"])
+                .await
+                .unwrap();
+            assert_eq!(change.version, 1);
+            assert!(change.invalid_labels.is_empty());
+
+            let labeled = name.as_str().get_labeled(&config, "stable").await.unwrap();
+            assert_eq!(labeled.value, "off");
+        },
+        delete_parameters(&config, &[&name]),
+    )
+    .await;
+}
+
+#[tokio::test]
+#[ignore = "needs a local endpoint, e.g. LocalStack"]
+async fn missing_parameter_is_not_found() {
+    let config = local_config().await;
+    let name = format!("/{}/missing", unique_name("aws-secrets"));
+
+    let err = name.as_str().get_string(&config).await.unwrap_err();
+    assert!(err.is_not_found(), "{:?}", err);
+}
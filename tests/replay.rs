@@ -0,0 +1,616 @@
+use std::time::Duration;
+
+use aws_smithy_client::erase::DynConnector;
+use aws_smithy_client::never::NeverConnector;
+use futures_util::TryStreamExt;
+use serde::Deserialize;
+
+use aws_secrets::config::SdkConfig;
+use aws_secrets::failover::RegionFailover;
+use aws_secrets::fallback::CredentialFallback;
+use aws_secrets::test_util::{responses, ReplayConfig};
+use aws_secrets::timeout::Timeouts;
+use aws_secrets::{
+    describe_parameters, list_secrets, Error, ParameterFilter, ParameterType, ReplicaRegion,
+    RotationConfig, RotationRules, SSMParamExt, SecretFilter, SecretSummary, SecretsExt,
+};
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+struct Credentials {
+    username: String,
+    password: String,
+}
+
+#[tokio::test]
+async fn test_get_secret() {
+    let (config, replay) = ReplayConfig::new()
+        .respond(responses::secret_string(
+            "my-creds",
+            r#"{"Username":"my-user","Password":"my-pass12345"}"#,
+        ))
+        .build();
+
+    let creds: Credentials = "my-creds".get_secret(&config).await.unwrap();
+
+    assert_eq!(
+        creds,
+        Credentials {
+            username: "my-user".to_owned(),
+            password: "my-pass12345".to_owned(),
+        }
+    );
+
+    let requests = replay.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].target, "secretsmanager.GetSecretValue");
+    assert_eq!(requests[0].body["SecretId"], "my-creds");
+}
+
+#[tokio::test]
+async fn test_get_secret_invalid_json() {
+    let (config, _) = ReplayConfig::new()
+        .respond(responses::secret_string("my-creds", "not json"))
+        .build();
+
+    let err = "my-creds"
+        .get_secret::<Credentials>(&config)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::DeserializeError(_)));
+}
+
+#[tokio::test]
+async fn test_get_secret_not_found() {
+    let (config, _) = ReplayConfig::new()
+        .respond(responses::secret_not_found())
+        .build();
+
+    let err = "missing".get_secret_string(&config).await.unwrap_err();
+
+    assert!(matches!(err, Error::ReadSecret { ref secret_name, .. } if secret_name == "missing"));
+    assert!(err.is_not_found());
+}
+
+#[tokio::test]
+async fn test_get_secret_binary() {
+    let (config, _) = ReplayConfig::new()
+        .respond(responses::json(
+            200,
+            serde_json::json!({
+                "Name": "my-binary",
+                "VersionId": "EXAMPLE1-90ab-cdef-fedc-ba987EXAMPLE",
+                "SecretBinary": "aGVsbG8=",
+            }),
+        ))
+        .build();
+
+    let err = "my-binary".get_secret_string(&config).await.unwrap_err();
+
+    assert!(
+        matches!(err, Error::MissingSecretString { ref secret_name } if secret_name == "my-binary")
+    );
+}
+
+#[tokio::test]
+async fn test_get_string_list() {
+    let (config, replay) = ReplayConfig::new()
+        .respond(responses::parameter(
+            "/my/list",
+            "a,b,something else,test123",
+            "StringList",
+        ))
+        .build();
+
+    let values = "/my/list".get_string_list(&config).await.unwrap();
+
+    assert_eq!(values, ["a", "b", "something else", "test123"]);
+    assert_eq!(replay.requests()[0].target, "AmazonSSM.GetParameter");
+    assert_eq!(replay.requests()[0].body["WithDecryption"], false);
+}
+
+#[tokio::test]
+async fn test_get_string_list_not_found() {
+    let (config, _) = ReplayConfig::new()
+        .respond(responses::parameter_not_found())
+        .build();
+
+    let err = "/my/list".get_string_list(&config).await.unwrap_err();
+
+    assert!(matches!(err, Error::ReadParam { .. }));
+    assert_eq!(err.code(), Some("ParameterNotFound"));
+    assert!(err.is_not_found());
+}
+
+#[tokio::test]
+async fn test_throttling_without_retries() {
+    let (config, replay) = ReplayConfig::new().respond(responses::throttling()).build();
+
+    let err = "/my/param".get_secure_string(&config).await.unwrap_err();
+
+    assert_eq!(err.code(), Some("ThrottlingException"));
+    assert!(!err.is_not_found());
+    assert_eq!(replay.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn test_throttling_is_retried() {
+    let (config, replay) = ReplayConfig::new()
+        .max_attempts(3)
+        .respond(responses::throttling())
+        .respond(responses::internal_error())
+        .respond(responses::parameter("/my/param", "value", "SecureString"))
+        .build();
+
+    let value = "/my/param".get_secure_string(&config).await.unwrap();
+
+    assert_eq!(value, "value");
+    assert_eq!(replay.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn test_credential_fallback() {
+    let (config, replay) = ReplayConfig::new()
+        .respond(responses::secret_string(
+            "my-creds",
+            r#"{"Username":"my-user","Password":"new-pass"}"#,
+        ))
+        .respond(responses::secret_not_found())
+        .respond(responses::secret_string(
+            "my-creds",
+            r#"{"Username":"my-user","Password":"old-pass"}"#,
+        ))
+        .build();
+    let mut invalidated = false;
+
+    let login = CredentialFallback::new(&config, "my-creds")
+        .on_invalidate(|| invalidated = true)
+        .try_login(|creds: Credentials| async move {
+            if creds.password == "old-pass" {
+                Ok(creds.password)
+            } else {
+                Err("login failed")
+            }
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(login.value, "old-pass");
+    assert_eq!(login.version_stage, "AWSPREVIOUS");
+    assert!(invalidated);
+
+    let stages: Vec<_> = replay
+        .requests()
+        .iter()
+        .map(|r| r.body["VersionStage"].clone())
+        .collect();
+    assert_eq!(stages, ["AWSCURRENT", "AWSPENDING", "AWSPREVIOUS"]);
+}
+
+#[tokio::test]
+async fn test_set_rotation() {
+    let (config, replay) = ReplayConfig::new()
+        .respond(responses::json(
+            200,
+            serde_json::json!({ "ARN": "arn", "Name": "my-secret" }),
+        ))
+        .build();
+
+    let rotation = RotationConfig::new(RotationRules::schedule("rate(10 days)").duration("2h"))
+        .lambda_arn("arn:aws:lambda:us-east-1:123456789012:function:rotate")
+        .rotate_immediately(false);
+    let result = "my-secret".set_rotation(&config, &rotation).await.unwrap();
+
+    assert_eq!(result.name, "my-secret");
+    assert_eq!(result.version_id, None);
+
+    let body = &replay.requests()[0].body;
+    assert_eq!(body["RotationRules"]["ScheduleExpression"], "rate(10 days)");
+    assert_eq!(body["RotationRules"]["Duration"], "2h");
+    assert_eq!(body["RotateImmediately"], false);
+}
+
+#[tokio::test]
+async fn test_list_secrets_paginates() {
+    let (config, replay) = ReplayConfig::new()
+        .respond(responses::json(
+            200,
+            serde_json::json!({
+                "SecretList": [{ "Name": "app/a" }, { "Name": "app/b" }],
+                "NextToken": "page-2",
+            }),
+        ))
+        .respond(responses::json(
+            200,
+            serde_json::json!({ "SecretList": [{ "Name": "app/c", "RotationEnabled": true }] }),
+        ))
+        .build();
+
+    let filter = SecretFilter::new().name_prefix("app/").tag_key("team");
+    let secrets: Vec<SecretSummary> = list_secrets(&config, &filter).try_collect().await.unwrap();
+
+    let names: Vec<_> = secrets.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["app/a", "app/b", "app/c"]);
+    assert!(secrets[2].rotation_enabled);
+
+    let requests = replay.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].body["Filters"][0]["Key"], "name");
+    assert_eq!(requests[0].body["Filters"][1]["Key"], "tag-key");
+    assert!(requests[0].body.get("NextToken").is_none());
+    assert_eq!(requests[1].body["NextToken"], "page-2");
+}
+
+#[tokio::test]
+async fn test_describe_parameters() {
+    let (config, replay) = ReplayConfig::new()
+        .respond(responses::json(
+            200,
+            serde_json::json!({
+                "Parameters": [{ "Name": "/app/a", "Type": "SecureString", "Version": 3 }],
+                "NextToken": "page-2",
+            }),
+        ))
+        .respond(responses::json(
+            200,
+            serde_json::json!({ "Parameters": [] }),
+        ))
+        .build();
+
+    let filter = ParameterFilter::new()
+        .path("/app", false)
+        .param_type(ParameterType::SecureString)
+        .tag("team", None);
+    let params: Vec<_> = describe_parameters(&config, &filter)
+        .try_collect()
+        .await
+        .unwrap();
+
+    assert_eq!(params.len(), 1);
+    assert_eq!(params[0].name, "/app/a");
+    assert_eq!(params[0].param_type.as_deref(), Some("SecureString"));
+    assert_eq!(params[0].version, 3);
+
+    let requests = replay.requests();
+    assert_eq!(requests[0].target, "AmazonSSM.DescribeParameters");
+    let filters = &requests[0].body["ParameterFilters"];
+    assert_eq!(filters[0]["Key"], "Path");
+    assert_eq!(filters[0]["Option"], "OneLevel");
+    assert_eq!(filters[1]["Values"][0], "SecureString");
+    assert_eq!(filters[2]["Key"], "tag:team");
+    assert!(filters[2].get("Values").is_none());
+    assert_eq!(requests[1].body["NextToken"], "page-2");
+}
+
+#[tokio::test]
+async fn test_rollback_parameter() {
+    let (config, replay) = ReplayConfig::new()
+        .respond(responses::json(
+            200,
+            serde_json::json!({
+                "Parameters": [
+                    {
+                        "Name": "/my/param", "Version": 1, "Value": "old", "Type": "SecureString", "KeyId": "alias/app",
+                        "Tier": "Standard", "AllowedPattern": "^[a-z]+$", "DataType": "text",
+                    },
+                    {
+                        "Name": "/my/param", "Version": 2, "Value": "new", "Type": "SecureString", "KeyId": "alias/app",
+                        "Tier": "Advanced", "AllowedPattern": "^[a-z0-9]+$", "DataType": "text", "Labels": ["prod"],
+                    },
+                ],
+            }),
+        ))
+        .respond(responses::json(200, serde_json::json!({ "Version": 3 })))
+        .build();
+
+    let output = "/my/param".rollback_parameter(&config, 1).await.unwrap();
+
+    assert_eq!(output.version, 3);
+    let requests = replay.requests();
+    assert_eq!(requests[0].target, "AmazonSSM.GetParameterHistory");
+    assert_eq!(requests[1].target, "AmazonSSM.PutParameter");
+    assert_eq!(requests[1].body["Value"], "old");
+    assert_eq!(requests[1].body["Type"], "SecureString");
+    assert_eq!(requests[1].body["KeyId"], "alias/app");
+    // The parameter is now `Advanced`, which can't be rolled back to `Standard`.
+    assert!(requests[1].body.get("Tier").is_none());
+    assert!(requests[1].body.get("AllowedPattern").is_none());
+    assert!(requests[1].body.get("DataType").is_none());
+    assert_eq!(requests[1].body["Overwrite"], true);
+}
+
+#[tokio::test]
+async fn test_rollback_parameter_unknown_version() {
+    let (config, _) = ReplayConfig::new()
+        .respond(responses::json(
+            200,
+            serde_json::json!({ "Parameters": [] }),
+        ))
+        .build();
+
+    let err = "/my/param"
+        .rollback_parameter(&config, 7)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::NotFound { ref name } if name == "/my/param:7"));
+}
+
+#[tokio::test]
+async fn test_move_label() {
+    let (config, replay) = ReplayConfig::new()
+        .respond(responses::json(
+            200,
+            serde_json::json!({ "InvalidLabels": ["bad label"], "ParameterVersion": 4 }),
+        ))
+        .respond(responses::json(
+            200,
+            serde_json::json!({
+                "Parameter": { "Name": "/my/param", "Value": "v4", "Version": 4, "Selector": ":prod", "Type": "String" }
+            }),
+        ))
+        .build();
+
+    let change = "/my/param"
+        .label_parameter_version(&config, Some(4), &["prod", "bad label"])
+        .await
+        .unwrap();
+    assert_eq!(change.version, 4);
+    assert_eq!(change.labels, ["prod"]);
+    assert_eq!(change.invalid_labels, ["bad label"]);
+
+    let param = "/my/param".get_labeled(&config, "prod").await.unwrap();
+    assert_eq!(param.value, "v4");
+    assert_eq!(param.version, 4);
+    assert_eq!(param.selector.as_deref(), Some(":prod"));
+
+    let requests = replay.requests();
+    assert_eq!(requests[0].target, "AmazonSSM.LabelParameterVersion");
+    assert_eq!(requests[0].body["ParameterVersion"], 4);
+    assert_eq!(requests[1].body["Name"], "/my/param:prod");
+    assert_eq!(requests[1].body["WithDecryption"], true);
+}
+
+#[tokio::test]
+async fn test_promote_previous_version() {
+    let (config, replay) = ReplayConfig::new()
+        .respond(responses::json(
+            200,
+            serde_json::json!({
+                "Name": "my-creds",
+                "Versions": [
+                    { "VersionId": "v1", "VersionStages": ["AWSPREVIOUS"] },
+                    { "VersionId": "v2", "VersionStages": ["AWSCURRENT"] },
+                ],
+            }),
+        ))
+        .respond(responses::describe_secret(
+            "my-creds",
+            &[("v1", &["AWSPREVIOUS"]), ("v2", &["AWSCURRENT"])],
+        ))
+        .respond(responses::ok())
+        .build();
+
+    let versions = "my-creds"
+        .list_secret_version_ids(&config, false)
+        .await
+        .unwrap();
+    let previous = versions
+        .iter()
+        .find(|v| v.has_stage("AWSPREVIOUS"))
+        .unwrap();
+    "my-creds"
+        .promote_secret_version(&config, &previous.version_id)
+        .await
+        .unwrap();
+
+    let requests = replay.requests();
+    assert_eq!(requests[0].target, "secretsmanager.ListSecretVersionIds");
+    assert_eq!(requests[0].body["IncludeDeprecated"], false);
+    assert_eq!(
+        requests[2].target,
+        "secretsmanager.UpdateSecretVersionStage"
+    );
+    assert_eq!(requests[2].body["VersionStage"], "AWSCURRENT");
+    assert_eq!(requests[2].body["MoveToVersionId"], "v1");
+    assert_eq!(requests[2].body["RemoveFromVersionId"], "v2");
+}
+
+#[tokio::test]
+async fn test_resource_policy() {
+    let policy = serde_json::json!({
+        "Version": "2012-10-17",
+        "Statement": [{
+            "Effect": "Allow",
+            "Principal": { "AWS": "arn:aws:iam::123456789012:root" },
+            "Action": "secretsmanager:GetSecretValue",
+            "Resource": "*",
+        }],
+    });
+    let (config, replay) = ReplayConfig::new()
+        .respond(responses::json(
+            200,
+            serde_json::json!({ "PolicyValidationPassed": true, "ValidationErrors": [] }),
+        ))
+        .respond(responses::ok())
+        .respond(responses::json(
+            200,
+            serde_json::json!({ "Name": "shared", "ResourcePolicy": policy.to_string() }),
+        ))
+        .build();
+
+    let validation = "shared"
+        .validate_resource_policy(&config, &policy)
+        .await
+        .unwrap();
+    assert!(validation.passed);
+    "shared"
+        .put_resource_policy(&config, &policy, Some(true))
+        .await
+        .unwrap();
+    let attached = "shared".get_resource_policy(&config).await.unwrap();
+
+    assert_eq!(attached, Some(policy.clone()));
+    let requests = replay.requests();
+    assert_eq!(requests[1].target, "secretsmanager.PutResourcePolicy");
+    assert_eq!(requests[1].body["BlockPublicPolicy"], true);
+    let sent: serde_json::Value =
+        serde_json::from_str(requests[1].body["ResourcePolicy"].as_str().unwrap()).unwrap();
+    assert_eq!(sent, policy);
+}
+
+#[tokio::test]
+async fn test_replica_fallback() {
+    let (primary, _) = ReplayConfig::new()
+        .respond(responses::internal_error())
+        .build();
+    let (replica, replica_replay) = ReplayConfig::new()
+        .region("us-west-2")
+        .respond(responses::secret_string("my-creds", "from-replica"))
+        .build();
+
+    let value = "my-creds"
+        .get_secret_string_with_replicas(&primary, &[replica])
+        .await
+        .unwrap();
+
+    assert_eq!(value, "from-replica");
+    assert_eq!(replica_replay.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn test_replica_fallback_returns_last_error() {
+    let (primary, _) = ReplayConfig::new()
+        .respond(responses::internal_error())
+        .build();
+    let (replica, _) = ReplayConfig::new()
+        .region("us-west-2")
+        .respond(responses::throttling())
+        .build();
+
+    let err = "my-creds"
+        .get_secret_string_with_replicas(&primary, &[replica])
+        .await
+        .unwrap_err();
+
+    assert_eq!(err.code(), Some("ThrottlingException"));
+}
+
+#[tokio::test]
+async fn test_replica_fallback_not_found() {
+    let (primary, _) = ReplayConfig::new()
+        .respond(responses::secret_not_found())
+        .build();
+    let (replica, replica_replay) = ReplayConfig::new()
+        .region("us-west-2")
+        .respond(responses::secret_string("my-creds", "from-replica"))
+        .build();
+
+    let err = "my-creds"
+        .get_secret_string_with_replicas(&primary, &[replica])
+        .await
+        .unwrap_err();
+
+    assert!(err.is_not_found());
+    assert!(replica_replay.requests().is_empty());
+}
+
+#[tokio::test]
+async fn test_replicate_secret() {
+    let (config, replay) = ReplayConfig::new()
+        .respond(responses::json(
+            200,
+            serde_json::json!({
+                "ARN": "arn",
+                "ReplicationStatus": [{ "Region": "eu-west-1", "Status": "InProgress" }],
+            }),
+        ))
+        .build();
+
+    let status = "my-creds"
+        .replicate_secret_to_regions(
+            &config,
+            &[ReplicaRegion::new("eu-west-1").kms_key_id("alias/replica")],
+            false,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(status[0].region, "eu-west-1");
+    assert!(!status[0].is_in_sync());
+    let body = &replay.requests()[0].body;
+    assert_eq!(body["AddReplicaRegions"][0]["Region"], "eu-west-1");
+    assert_eq!(body["AddReplicaRegions"][0]["KmsKeyId"], "alias/replica");
+}
+
+#[tokio::test]
+async fn test_region_failover() {
+    let (primary, _) = ReplayConfig::new().respond(responses::throttling()).build();
+    let (secondary, _) = ReplayConfig::new()
+        .region("us-west-2")
+        .respond(responses::parameter("/my/param", "value", "String"))
+        .build();
+
+    let failover = RegionFailover::from_configs([primary, secondary]);
+    let served = failover.get_parameter("/my/param", false).await.unwrap();
+
+    assert_eq!(served.value, "value");
+    assert_eq!(served.region, "us-west-2");
+}
+
+#[tokio::test]
+async fn test_region_failover_stops_on_not_found() {
+    let (primary, _) = ReplayConfig::new()
+        .respond(responses::secret_not_found())
+        .build();
+    let (secondary, secondary_replay) = ReplayConfig::new()
+        .region("us-west-2")
+        .respond(responses::secret_string("my-creds", "value"))
+        .build();
+
+    let failover = RegionFailover::from_configs([primary, secondary]);
+    let err = failover.get_secret_string("my-creds").await.unwrap_err();
+
+    assert!(err.is_not_found());
+    assert!(secondary_replay.requests().is_empty());
+}
+
+/// Returns a config whose connection never responds, with a short total
+/// timeout.
+fn unresponsive_config() -> SdkConfig {
+    let (config, _) = ReplayConfig::new().build();
+    let config = config
+        .to_builder()
+        .http_connector(DynConnector::new(NeverConnector::new()))
+        .build();
+    Timeouts::new()
+        .total(Duration::from_millis(50))
+        .apply(&config)
+}
+
+#[tokio::test]
+async fn test_timeout() {
+    let config = unresponsive_config();
+
+    let err = "my-creds".get_secret_string(&config).await.unwrap_err();
+
+    assert!(err.is_timeout());
+    assert!(err.is_transient());
+    assert!(
+        matches!(err, Error::Timeout { resource: Some(ref resource), .. } if resource == "my-creds")
+    );
+}
+
+#[tokio::test]
+async fn test_list_timeout() {
+    let config = unresponsive_config();
+
+    let err = "prefix"
+        .list_secrets(&config)
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::Timeout { resource: None, .. }));
+}
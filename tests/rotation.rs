@@ -0,0 +1,142 @@
+use async_trait::async_trait;
+
+use aws_secrets::rotation::{BoxError, RotationContext, RotationEvent, RotationHandler, Rotator};
+use aws_secrets::test_util::{responses, ReplayConfig};
+use aws_secrets::Error;
+
+const TOKEN: &str = "EXAMPLE2-90ab-cdef-fedc-ba987EXAMPLE";
+const CURRENT: &str = "EXAMPLE1-90ab-cdef-fedc-ba987EXAMPLE";
+
+struct AppendRotation;
+
+#[async_trait]
+impl RotationHandler for AppendRotation {
+    async fn create_secret(&self, _: &RotationContext, current: &str) -> Result<String, BoxError> {
+        Ok(format!("{current}-next"))
+    }
+
+    async fn set_secret(&self, _: &RotationContext, _: &str, _: &str) -> Result<(), BoxError> {
+        Ok(())
+    }
+
+    async fn test_secret(&self, _: &RotationContext, pending: &str) -> Result<(), BoxError> {
+        if pending.ends_with("-next") {
+            Ok(())
+        } else {
+            Err("login failed".into())
+        }
+    }
+}
+
+fn event(step: &str) -> RotationEvent {
+    serde_json::from_value(serde_json::json!({
+        "Step": step,
+        "SecretId": "my-secret",
+        "ClientRequestToken": TOKEN,
+    }))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_create_secret() {
+    let (config, replay) = ReplayConfig::new()
+        .respond(responses::describe_secret(
+            "my-secret",
+            &[(CURRENT, &["AWSCURRENT"]), (TOKEN, &["AWSPENDING"])],
+        ))
+        .respond(responses::secret_string("my-secret", "value"))
+        .respond(responses::secret_not_found())
+        .respond(responses::ok())
+        .build();
+
+    Rotator::new(config, AppendRotation)
+        .handle(&event("createSecret"))
+        .await
+        .unwrap();
+
+    let requests = replay.requests();
+    assert_eq!(requests.len(), 4);
+    assert_eq!(requests[3].target, "secretsmanager.PutSecretValue");
+    assert_eq!(requests[3].body["SecretString"], "value-next");
+    assert_eq!(requests[3].body["ClientRequestToken"], TOKEN);
+    assert_eq!(requests[3].body["VersionStages"][0], "AWSPENDING");
+}
+
+#[tokio::test]
+async fn test_create_secret_is_idempotent() {
+    let (config, replay) = ReplayConfig::new()
+        .respond(responses::describe_secret(
+            "my-secret",
+            &[(CURRENT, &["AWSCURRENT"]), (TOKEN, &["AWSPENDING"])],
+        ))
+        .respond(responses::secret_string("my-secret", "value"))
+        .respond(responses::secret_string("my-secret", "value-next"))
+        .build();
+
+    Rotator::new(config, AppendRotation)
+        .handle(&event("createSecret"))
+        .await
+        .unwrap();
+
+    assert_eq!(replay.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn test_test_secret_failure() {
+    let (config, _) = ReplayConfig::new()
+        .respond(responses::describe_secret(
+            "my-secret",
+            &[(CURRENT, &["AWSCURRENT"]), (TOKEN, &["AWSPENDING"])],
+        ))
+        .respond(responses::secret_string("my-secret", "wrong"))
+        .build();
+
+    let err = Rotator::new(config, AppendRotation)
+        .handle(&event("testSecret"))
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::Rotation { ref step, .. } if step == "testSecret"));
+}
+
+#[tokio::test]
+async fn test_finish_secret() {
+    let (config, replay) = ReplayConfig::new()
+        .respond(responses::describe_secret(
+            "my-secret",
+            &[(CURRENT, &["AWSCURRENT"]), (TOKEN, &["AWSPENDING"])],
+        ))
+        .respond(responses::ok())
+        .build();
+
+    Rotator::new(config, AppendRotation)
+        .handle(&event("finishSecret"))
+        .await
+        .unwrap();
+
+    let requests = replay.requests();
+    assert_eq!(
+        requests[1].target,
+        "secretsmanager.UpdateSecretVersionStage"
+    );
+    assert_eq!(requests[1].body["VersionStage"], "AWSCURRENT");
+    assert_eq!(requests[1].body["MoveToVersionId"], TOKEN);
+    assert_eq!(requests[1].body["RemoveFromVersionId"], CURRENT);
+}
+
+#[tokio::test]
+async fn test_already_current_is_noop() {
+    let (config, replay) = ReplayConfig::new()
+        .respond(responses::describe_secret(
+            "my-secret",
+            &[(TOKEN, &["AWSCURRENT"])],
+        ))
+        .build();
+
+    Rotator::new(config, AppendRotation)
+        .handle(&event("finishSecret"))
+        .await
+        .unwrap();
+
+    assert_eq!(replay.requests().len(), 1);
+}
@@ -0,0 +1,189 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+use aws_secrets::failover::RegionFailover;
+use aws_secrets::refresh::Refresher;
+use aws_secrets::store::MemoryStore;
+use aws_secrets::test_util::{responses, ReplayConfig};
+use aws_secrets::SecretsExt;
+
+/// A field recorded on a span (or event), as `(span name, field, value)`.
+type Recorded = (String, String, String);
+
+/// A subscriber which captures every field recorded by this crate.
+#[derive(Clone, Default)]
+struct Capture {
+    next_id: Arc<AtomicU64>,
+    names: Arc<Mutex<Vec<(u64, &'static str)>>>,
+    fields: Arc<Mutex<Vec<Recorded>>>,
+}
+
+struct Visitor<'a> {
+    span: &'a str,
+    fields: &'a mut Vec<Recorded>,
+}
+
+impl Visit for Visitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.fields.push((
+            self.span.to_owned(),
+            field.name().to_owned(),
+            value.to_owned(),
+        ));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.fields.push((
+            self.span.to_owned(),
+            field.name().to_owned(),
+            format!("{value:?}"),
+        ));
+    }
+}
+
+impl Capture {
+    fn fields(&self, span: &str) -> Vec<(String, String)> {
+        self.fields
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(name, _, _)| name == span)
+            .map(|(_, field, value)| (field.clone(), value.clone()))
+            .collect()
+    }
+
+    fn field(&self, span: &str, field: &str) -> Option<String> {
+        self.fields(span)
+            .into_iter()
+            .find(|(name, _)| name == field)
+            .map(|(_, value)| value)
+    }
+
+    fn span_name(&self, id: &Id) -> &'static str {
+        let names = self.names.lock().unwrap();
+        names
+            .iter()
+            .find(|(n, _)| *n == id.into_u64())
+            .map_or("", |(_, name)| name)
+    }
+}
+
+impl Subscriber for Capture {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        // Only this crate's spans, not those of the SDK.
+        metadata.target().starts_with("aws_secrets")
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let name = span.metadata().name();
+        self.names.lock().unwrap().push((id, name));
+        span.record(&mut Visitor {
+            span: name,
+            fields: &mut self.fields.lock().unwrap(),
+        });
+        Id::from_u64(id)
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        values.record(&mut Visitor {
+            span: self.span_name(span),
+            fields: &mut self.fields.lock().unwrap(),
+        });
+    }
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        event.record(&mut Visitor {
+            span: "event",
+            fields: &mut self.fields.lock().unwrap(),
+        });
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+#[tokio::test]
+async fn requests_are_traced_without_values() {
+    let capture = Capture::default();
+    let _guard = tracing::subscriber::set_default(capture.clone());
+    let (config, _) = ReplayConfig::new()
+        .respond(responses::secret_string("my-creds", "hunter2"))
+        .respond(responses::secret_not_found())
+        .build();
+
+    "my-creds".get_secret_string(&config).await.unwrap();
+    let ok = capture.fields("aws_secrets");
+    "missing".get_secret_string(&config).await.unwrap_err();
+
+    assert!(ok.contains(&("service".to_owned(), "secretsmanager".to_owned())));
+    assert!(ok.contains(&("operation".to_owned(), "GetSecretValue".to_owned())));
+    assert!(ok.contains(&("resource".to_owned(), "my-creds".to_owned())));
+    assert!(ok.contains(&("region".to_owned(), "us-east-1".to_owned())));
+    assert!(ok.contains(&("attempt".to_owned(), "1".to_owned())));
+    assert!(ok.iter().any(|(field, _)| field == "latency_ms"));
+    assert!(!ok.iter().any(|(field, _)| field == "error"));
+    assert_eq!(
+        capture.field("aws_secrets", "error").as_deref(),
+        Some("ResourceNotFoundException")
+    );
+
+    let fields = capture.fields.lock().unwrap();
+    for (_, field, value) in fields.iter() {
+        assert!(!value.contains("hunter2"), "{field} recorded a value");
+        assert!(!value.contains("can't find"), "{field} recorded a message");
+    }
+}
+
+#[tokio::test]
+async fn failover_requests_record_their_attempt() {
+    let capture = Capture::default();
+    let _guard = tracing::subscriber::set_default(capture.clone());
+    let (config, _) = ReplayConfig::new()
+        .respond(responses::internal_error())
+        .respond(responses::secret_string("my-creds", "hunter2"))
+        .build();
+    let failover = RegionFailover::new(&config, ["us-east-1", "us-west-2"]);
+
+    failover.get_secret_string("my-creds").await.unwrap();
+
+    let attempts: Vec<_> = capture
+        .fields("aws_secrets")
+        .into_iter()
+        .filter(|(field, _)| field == "attempt")
+        .map(|(_, value)| value)
+        .collect();
+    assert_eq!(attempts, ["1", "2"]);
+}
+
+#[tokio::test]
+async fn refresher_lookups_record_cache_hits() {
+    let capture = Capture::default();
+    let _guard = tracing::subscriber::set_default(capture.clone());
+    let refresher = Refresher::builder(MemoryStore::new().with_secret("prod/db", "hunter2"))
+        .secret("prod/db")
+        .start()
+        .await
+        .unwrap();
+
+    refresher.secret("prod/db").unwrap();
+    assert!(refresher.parameter("/not/registered").is_none());
+
+    let fields = capture.fields("aws_secrets");
+    let hits: Vec<_> = fields
+        .iter()
+        .filter(|(field, _)| field == "cache.hit")
+        .map(|(_, value)| value.as_str())
+        .collect();
+    assert_eq!(hits, ["true", "false"]);
+    assert!(fields.contains(&("resource".to_owned(), "prod/db".to_owned())));
+    assert!(fields.contains(&("resource".to_owned(), "/not/registered".to_owned())));
+}